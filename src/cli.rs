@@ -35,6 +35,13 @@ pub enum Commands {
         /// Defaults to "dist" if not specified.
         #[arg(short, long, default_value = "dist")]
         dir: String,
+        /// Fail on missing template variables
+        ///
+        /// Reports the template, line, page and available context keys
+        /// instead of rendering missing variables as empty. Same as
+        /// `strict_templates = true` in the `[build]` section.
+        #[arg(long)]
+        strict: bool,
     },
     /// Serve a blog project
     ///
@@ -48,6 +55,58 @@ pub enum Commands {
         #[arg(short, long, default_value_t = 3000)]
         port: u16,
     },
+    /// Print the template context of a page
+    ///
+    /// Shows the JSON data a template receives, e.g. `context post hello-world`
+    /// or `context tag rust`. Useful when writing themes.
+    Context {
+        /// Page type: `post`, `index`, `archive`, `about`, `tags`, `tag`,
+        /// `category`, `series_list`, `series`, `authors`, `author`, `rss` or `404`
+        #[arg(required_unless_present = "schema")]
+        page: Option<String>,
+        /// Slug or name of a post, tag, category, series or author
+        name: Option<String>,
+        /// Site language of the page
+        #[arg(short, long)]
+        lang: Option<String>,
+        /// Print a JSON Schema of the context of every page type
+        #[arg(long, conflicts_with_all = ["page", "name"])]
+        schema: bool,
+    },
+    /// Manage the project's copy of the built-in theme
+    ///
+    /// Copies built-in templates and static files into the project and
+    /// brings local copies up to date with new releases.
+    Theme {
+        #[command(subcommand)]
+        action: ThemeCommands,
+    },
+}
+
+/// Actions of the `theme` command.
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum ThemeCommands {
+    /// Copy built-in theme files into the project
+    ///
+    /// Copies one file (e.g. `post.html` or `static/css/main.css`) or, without
+    /// an argument, the whole theme. Existing files are kept.
+    Eject {
+        /// Theme file to copy
+        file: Option<String>,
+        /// Overwrite existing files
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Show how local theme files differ from the built-in theme
+    Diff {
+        /// Theme file to compare (all by default)
+        file: Option<String>,
+    },
+    /// Merge changes of the built-in theme into local copies
+    ///
+    /// Performs a three-way merge against the theme version recorded at
+    /// `init` (or the last upgrade) and reports conflicts.
+    Upgrade,
 }
 
 /// Main CLI structure for the Zahuyach static site generator.
@@ -78,8 +137,21 @@ impl Cli {
     pub fn run(self) -> Result<String> {
         match self.command {
             Commands::Init { name } => commands::init::run(name.as_ref()),
-            Commands::Build { dir } => commands::build::run(dir),
+            Commands::Build { dir, strict } => commands::build::run(dir, strict),
             Commands::Serve { port } => commands::serve::run(port),
+            Commands::Context {
+                page,
+                name,
+                lang,
+                schema,
+            } => commands::context::run(page.as_deref(), name.as_deref(), lang.as_deref(), schema),
+            Commands::Theme { action } => match action {
+                ThemeCommands::Eject { file, force } => {
+                    commands::theme::eject(file.as_deref(), force)
+                }
+                ThemeCommands::Diff { file } => commands::theme::diff(file.as_deref()),
+                ThemeCommands::Upgrade => commands::theme::upgrade(),
+            },
         }
     }
 
@@ -3,7 +3,7 @@ use crate::error::Result;
 use crate::generator::SiteGenerator;
 use std::path::Path;
 
-pub fn run(output_dir: String) -> Result<String> {
+pub fn run(output_dir: String, strict: bool) -> Result<String> {
     // Проверяем, что мы в корне проекта (есть config.toml)
     let config_path = Path::new("config.toml");
     if !config_path.exists() {
@@ -18,6 +18,11 @@ pub fn run(output_dir: String) -> Result<String> {
     // Переопределяем output_dir если передан через аргумент
     config.build.output_dir = output_dir.clone();
 
+    // Флаг --strict включает строгие шаблоны поверх config.toml
+    if strict {
+        config.build.strict_templates = Some(true);
+    }
+
     // Создаем генератор и запускаем сборку
     let mut generator = SiteGenerator::new(config)?;
     generator.build()?;
@@ -31,7 +36,7 @@ mod tests {
 
     #[test]
     fn test_build_command() {
-        let result = run("dist".to_string());
+        let result = run("dist".to_string(), false);
         // This will fail in test environment without proper setup
         assert!(result.is_err() || result.is_ok());
     }
@@ -0,0 +1,294 @@
+use crate::config::Config;
+use crate::error::{Result, ZahuyachError};
+use crate::generator::SiteGenerator;
+use serde_json::{Map, Value, json};
+use std::path::Path;
+
+/// Prints the JSON context a template receives for one page, or with
+/// `schema` a JSON Schema of the context of every page type.
+///
+/// # Errors
+///
+/// Returns an error outside a project, when the site can't be loaded or
+/// when the page doesn't exist.
+pub fn run(
+    page_type: Option<&str>,
+    name: Option<&str>,
+    language: Option<&str>,
+    schema: bool,
+) -> Result<String> {
+    let config_path = Path::new("config.toml");
+    if !config_path.exists() {
+        return Err(ZahuyachError::InvalidInput(
+            "config.toml not found. Make sure you're in a Zahuyach project directory.".to_string(),
+        ));
+    }
+
+    let mut generator = SiteGenerator::new(Config::load(config_path)?)?;
+    generator.load()?;
+    if let Some(language) = language {
+        generator.set_language(language)?;
+    }
+
+    let output = if schema {
+        contexts_schema(&generator.sample_contexts()?)
+    } else {
+        let page_type = page_type.ok_or_else(|| {
+            ZahuyachError::InvalidInput("Specify a page type or --schema".to_string())
+        })?;
+        generator.page_context(page_type, name)?
+    };
+
+    serde_json::to_string_pretty(&output).map_err(|e| ZahuyachError::InvalidInput(e.to_string()))
+}
+
+/// Keys holding site-specific maps (data files, custom front matter),
+/// described as plain objects.
+const FREE_FORM_KEYS: [&str; 2] = ["data", "extra"];
+
+/// Builds a JSON Schema with a definition per page type, inferred from
+/// the sample contexts of each type.
+fn contexts_schema(contexts: &[(&str, Vec<Value>)]) -> Value {
+    let definitions: Map<String, Value> = contexts
+        .iter()
+        .map(|(page_type, variants)| {
+            let mut schema = variants
+                .iter()
+                .map(schema_of)
+                .reduce(merge)
+                .map_or_else(|| json!({ "type": "object" }), relax_nullable);
+            schema["title"] = json!(format!("Context of '{page_type}' pages"));
+            ((*page_type).to_string(), schema)
+        })
+        .collect();
+    let variants: Vec<Value> = definitions
+        .keys()
+        .map(|page_type| json!({ "$ref": format!("#/$defs/{page_type}") }))
+        .collect();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Zahuyach template context",
+        "anyOf": variants,
+        "$defs": definitions
+    })
+}
+
+/// Infers the schema of a value. Array items are merged into one schema.
+fn schema_of(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "type": "null" }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(number) if number.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => {
+            let mut schema = json!({ "type": "array" });
+            if let Some(items) = items.iter().map(schema_of).reduce(merge) {
+                schema["items"] = items;
+            }
+            schema
+        }
+        Value::Object(map) => json!({
+            "type": "object",
+            "properties": map
+                .iter()
+                .map(|(key, value)| {
+                    let schema = if FREE_FORM_KEYS.contains(&key.as_str()) {
+                        json!({ "type": "object" })
+                    } else {
+                        schema_of(value)
+                    };
+                    (key.clone(), schema)
+                })
+                .collect::<Map<_, _>>(),
+            "required": map.keys().collect::<Vec<_>>()
+        }),
+    }
+}
+
+/// Drops keys whose value may be `null` from `required`, at every level.
+fn relax_nullable(mut schema: Value) -> Value {
+    if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+        for property in properties.values_mut() {
+            *property = relax_nullable(property.take());
+        }
+        let nullable: Vec<String> = properties
+            .iter()
+            .filter(|(_, property)| is_nullable(property))
+            .map(|(key, _)| key.clone())
+            .collect();
+        if let Some(required) = schema.get_mut("required").and_then(Value::as_array_mut) {
+            required.retain(|key| !nullable.iter().any(|name| key == name));
+        }
+    }
+    if let Some(items) = schema.get_mut("items") {
+        *items = relax_nullable(items.take());
+    }
+    if let Some(variants) = schema.get_mut("anyOf").and_then(Value::as_array_mut) {
+        for variant in variants {
+            *variant = relax_nullable(variant.take());
+        }
+    }
+    schema
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    schema["type"] == "null"
+        || schema["anyOf"]
+            .as_array()
+            .is_some_and(|variants| variants.iter().any(is_nullable))
+}
+
+/// Merges two schemas: objects keep keys present in both as required,
+/// different types become `anyOf`.
+fn merge(a: Value, b: Value) -> Value {
+    if a == b {
+        return a;
+    }
+
+    match (a["type"].as_str(), b["type"].as_str()) {
+        (Some("object"), Some("object")) => {
+            let mut properties = a["properties"].as_object().cloned().unwrap_or_default();
+            for (key, schema) in b["properties"].as_object().into_iter().flatten() {
+                let merged = properties.remove(key).map_or_else(
+                    || schema.clone(),
+                    |existing| merge(existing, schema.clone()),
+                );
+                properties.insert(key.clone(), merged);
+            }
+            let required: Vec<&Value> = a["required"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|key| b["required"].as_array().is_some_and(|r| r.contains(key)))
+                .collect();
+            json!({ "type": "object", "properties": properties, "required": required })
+        }
+        (Some("array"), Some("array")) => match (a.get("items"), b.get("items")) {
+            (Some(x), Some(y)) => json!({ "type": "array", "items": merge(x.clone(), y.clone()) }),
+            (Some(_), None) => a,
+            _ => b,
+        },
+        (Some("integer"), Some("number")) | (Some("number"), Some("integer")) => {
+            json!({ "type": "number" })
+        }
+        _ => {
+            let mut variants: Vec<Value> = Vec::new();
+            for schema in [a, b] {
+                let nested = schema
+                    .get("anyOf")
+                    .and_then(Value::as_array)
+                    .map_or_else(|| vec![schema.clone()], Clone::clone);
+                for schema in nested {
+                    // Варианты одного типа сливаются, а не копятся
+                    match variants.iter_mut().find(|v| v["type"] == schema["type"]) {
+                        Some(variant) => *variant = merge(variant.take(), schema),
+                        None => variants.push(schema),
+                    }
+                }
+            }
+            if variants.len() == 1 {
+                variants.remove(0)
+            } else {
+                json!({ "anyOf": variants })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_of_merges_array_items() {
+        let schema = schema_of(&json!({
+            "posts": [
+                {"title": "A", "reading_time": 3, "image": null},
+                {"title": "B", "reading_time": 2.5, "image": "cover.png", "draft": true}
+            ]
+        }));
+
+        let items = &schema["properties"]["posts"]["items"];
+        assert_eq!(items["type"], "object");
+        assert_eq!(items["required"], json!(["image", "reading_time", "title"]));
+        assert_eq!(
+            relax_nullable(schema.clone())["properties"]["posts"]["items"]["required"],
+            json!(["reading_time", "title"])
+        );
+        assert_eq!(
+            items["properties"]["reading_time"],
+            json!({"type": "number"})
+        );
+        assert_eq!(
+            items["properties"]["image"],
+            json!({"anyOf": [{"type": "null"}, {"type": "string"}]})
+        );
+        assert_eq!(items["properties"]["draft"], json!({"type": "boolean"}));
+
+        let schema = schema_of(&json!([null, "a", null, 1, "b"]));
+        assert_eq!(
+            schema["items"],
+            json!({"anyOf": [{"type": "null"}, {"type": "string"}, {"type": "integer"}]})
+        );
+    }
+
+    #[test]
+    fn test_contexts_schema_defines_every_page_type() {
+        let schema = contexts_schema(&[
+            ("index", vec![json!({"posts": []})]),
+            ("tag", vec![json!({"tag": {"name": "rust"}})]),
+        ]);
+
+        assert_eq!(
+            schema["anyOf"],
+            json!([{"$ref": "#/$defs/index"}, {"$ref": "#/$defs/tag"}])
+        );
+        assert_eq!(
+            schema["$defs"]["index"]["properties"]["posts"]["type"],
+            "array"
+        );
+        assert_eq!(
+            schema["$defs"]["tag"]["properties"]["tag"]["required"],
+            json!(["name"])
+        );
+    }
+
+    #[test]
+    fn test_contexts_schema_merges_variants() {
+        let schema = contexts_schema(&[(
+            "post",
+            vec![
+                json!({
+                    "post": {"image": "a.png", "tags": ["rust"], "extra": {"mood": "happy"}},
+                    "series": {"name": "Rust"},
+                    "data": {"menu": ["home"]}
+                }),
+                json!({
+                    "post": {"image": null, "tags": [], "extra": {}},
+                    "series": null,
+                    "data": {}
+                }),
+            ],
+        )]);
+
+        let post = &schema["$defs"]["post"];
+        assert_eq!(post["required"], json!(["data", "post"]));
+        assert_eq!(
+            post["properties"]["series"]["anyOf"],
+            json!([
+                {"type": "object", "properties": {"name": {"type": "string"}}, "required": ["name"]},
+                {"type": "null"}
+            ])
+        );
+        assert_eq!(post["properties"]["data"], json!({"type": "object"}));
+        let fields = &post["properties"]["post"];
+        assert_eq!(fields["required"], json!(["extra", "tags"]));
+        assert_eq!(fields["properties"]["extra"], json!({"type": "object"}));
+        assert_eq!(
+            fields["properties"]["tags"],
+            json!({"type": "array", "items": {"type": "string"}})
+        );
+    }
+}
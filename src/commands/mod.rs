@@ -1,3 +1,5 @@
 pub mod build;
+pub mod context;
 pub mod init;
 pub mod serve;
+pub mod theme;
@@ -0,0 +1,459 @@
+//! `zahuyach theme`: copying the embedded basic theme into a project and
+//! keeping ejected copies up to date.
+//!
+//! Theme files are addressed by their path inside the theme, e.g.
+//! `templates/post.html` or `static/css/main.css`. The versions written by
+//! `init`, `eject` and `upgrade` are recorded in [`templates::THEME_BASE_DIR`]
+//! and serve as the common ancestor for three-way merges.
+
+use crate::config::Config;
+use crate::error::{Result, ZahuyachError};
+use crate::templates::{self, BasicTemplate};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Copies embedded theme files into the project: one file, or all of them.
+///
+/// Existing files are kept unless `force` is set.
+///
+/// # Errors
+///
+/// Fails outside a project, for unknown theme files and on I/O errors.
+pub fn eject(file: Option<&str>, force: bool) -> Result<String> {
+    Project::load(Path::new("."))?.eject(file, force)
+}
+
+/// Shows unified diffs between the embedded theme and local copies.
+///
+/// # Errors
+///
+/// Fails outside a project, for unknown theme files and on I/O errors.
+pub fn diff(file: Option<&str>) -> Result<String> {
+    Project::load(Path::new("."))?.diff(file)
+}
+
+/// Merges changes of the embedded theme into local copies.
+///
+/// # Errors
+///
+/// Fails outside a project and on I/O errors. Merge conflicts are not
+/// errors: they are written with conflict markers and listed in the report.
+pub fn upgrade() -> Result<String> {
+    Project::load(Path::new("."))?.upgrade()
+}
+
+/// Where theme files live in a particular project.
+struct Project {
+    root: PathBuf,
+    templates_dir: PathBuf,
+    static_dir: PathBuf,
+    i18n_dir: PathBuf,
+}
+
+impl Project {
+    fn load(root: &Path) -> Result<Self> {
+        let config_path = root.join("config.toml");
+        if !config_path.exists() {
+            return Err(ZahuyachError::InvalidInput(
+                "config.toml not found. Make sure you're in a Zahuyach project directory."
+                    .to_string(),
+            ));
+        }
+        let config = Config::load(config_path)?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            templates_dir: root.join(&config.build.templates_dir),
+            static_dir: root.join(&config.build.static_dir),
+            i18n_dir: root.join(config.get_i18n_dir()),
+        })
+    }
+
+    /// Maps a theme file to its location in the project.
+    fn local_path(&self, theme_file: &str) -> Option<PathBuf> {
+        let (dir, relative) = theme_file.split_once('/')?;
+        match dir {
+            templates::TEMPLATES_DIR => Some(self.templates_dir.join(relative)),
+            templates::STATIC_DIR => Some(self.static_dir.join(relative)),
+            templates::I18N_DIR => Some(self.i18n_dir.join(relative)),
+            _ => None,
+        }
+    }
+
+    fn base_path(&self, theme_file: &str) -> PathBuf {
+        self.root.join(templates::THEME_BASE_DIR).join(theme_file)
+    }
+
+    /// Resolves the `file` argument to theme files. Besides full theme
+    /// paths, template paths like `post.html` or `partials/post_card` are
+    /// accepted.
+    fn theme_files(file: Option<&str>) -> Result<Vec<String>> {
+        let all = templates::theme_files();
+        let Some(file) = file else {
+            return Ok(all);
+        };
+
+        let file = file.trim_start_matches("./");
+        let candidates = [
+            file.to_string(),
+            format!("{}/{file}", templates::TEMPLATES_DIR),
+            format!("{}/{file}.html", templates::TEMPLATES_DIR),
+            format!("{}/{file}", templates::STATIC_DIR),
+            format!("{}/{file}", templates::I18N_DIR),
+        ];
+        candidates
+            .into_iter()
+            .find(|candidate| all.contains(candidate))
+            .map(|found| vec![found])
+            .ok_or_else(|| {
+                ZahuyachError::InvalidInput(format!("'{file}' is not a file of the built-in theme"))
+            })
+    }
+
+    fn eject(&self, file: Option<&str>, force: bool) -> Result<String> {
+        let mut report = String::new();
+        let (mut copied, mut skipped) = (0, 0);
+
+        for theme_file in Self::theme_files(file)? {
+            let Some(target) = self.local_path(&theme_file) else {
+                continue;
+            };
+            if target.exists() && !force {
+                let _ = writeln!(report, "  skipped (exists): {}", target.display());
+                skipped += 1;
+                continue;
+            }
+
+            let data = embedded(&theme_file)?;
+            write_file(&target, &data)?;
+            write_file(&self.base_path(&theme_file), &data)?;
+            let _ = writeln!(report, "  ejected: {}", target.display());
+            copied += 1;
+        }
+
+        templates::record_theme_version(&self.root)?;
+        let hint = if skipped > 0 {
+            " (use --force to overwrite)"
+        } else {
+            ""
+        };
+        Ok(format!(
+            "{report}Ejected {copied} file(s), skipped {skipped}{hint}"
+        ))
+    }
+
+    fn diff(&self, file: Option<&str>) -> Result<String> {
+        let mut report = String::new();
+        let mut changed = 0;
+
+        for theme_file in Self::theme_files(file)? {
+            let Some(local_path) = self.local_path(&theme_file) else {
+                continue;
+            };
+            let Ok(local) = fs::read(&local_path) else {
+                continue;
+            };
+            let upstream = embedded(&theme_file)?;
+            if local == upstream {
+                continue;
+            }
+            changed += 1;
+
+            match (String::from_utf8(upstream), String::from_utf8(local)) {
+                (Ok(upstream), Ok(local)) => {
+                    let patch = diffy::DiffOptions::new()
+                        .set_original_filename(format!("a/{theme_file}"))
+                        .set_modified_filename(format!("b/{}", local_path.display()))
+                        .create_patch(&upstream, &local)
+                        .to_string();
+                    report.push_str(&patch);
+                }
+                _ => {
+                    let _ = writeln!(report, "Binary files differ: {theme_file}");
+                }
+            }
+        }
+
+        if changed == 0 {
+            return Ok("Local theme files match the built-in theme".to_string());
+        }
+        let _ = write!(report, "{changed} file(s) differ from the built-in theme");
+        Ok(report)
+    }
+
+    fn upgrade(&self) -> Result<String> {
+        let mut upgrade = Upgrade::default();
+
+        for theme_file in templates::theme_files() {
+            let Some(local_path) = self.local_path(&theme_file) else {
+                continue;
+            };
+            // Не выгруженные файлы и так берутся из встроенной темы
+            let Ok(local) = fs::read(&local_path) else {
+                continue;
+            };
+            let upstream = embedded(&theme_file)?;
+            let base_path = self.base_path(&theme_file);
+            let base = fs::read(&base_path).ok();
+
+            let outcome = merge(base.as_deref(), &local, &upstream);
+            match &outcome {
+                Outcome::UpToDate => {}
+                Outcome::Updated => write_file(&local_path, &upstream)?,
+                Outcome::Merged(merged) | Outcome::Conflict(Some(merged)) => {
+                    write_file(&local_path, merged.as_bytes())?;
+                }
+                // Конфликт в бинарном файле не разрешен: прежняя база нужна,
+                // чтобы следующее обновление снова нашло изменение темы
+                Outcome::Conflict(None) => {
+                    upgrade.record(&outcome, &local_path);
+                    continue;
+                }
+                Outcome::NoBase => {
+                    upgrade.no_base.push(theme_file);
+                    continue;
+                }
+            }
+            write_file(&base_path, &upstream)?;
+            upgrade.record(&outcome, &local_path);
+        }
+
+        templates::record_theme_version(&self.root)?;
+        Ok(upgrade.report())
+    }
+}
+
+/// Result of upgrading one local file.
+enum Outcome {
+    UpToDate,
+    /// The file was unmodified and replaced with the new version
+    Updated,
+    /// Local and upstream changes were combined cleanly
+    Merged(String),
+    /// Merged text with conflict markers; `None` for binary files, which
+    /// are left as they are
+    Conflict(Option<String>),
+    /// The file was changed locally but its original version is unknown
+    NoBase,
+}
+
+/// Three-way merge of a local file with the upstream version, using `base`
+/// as the common ancestor.
+fn merge(base: Option<&[u8]>, local: &[u8], upstream: &[u8]) -> Outcome {
+    if local == upstream {
+        return Outcome::UpToDate;
+    }
+    let Some(base) = base else {
+        return Outcome::NoBase;
+    };
+    if local == base {
+        return Outcome::Updated;
+    }
+    if upstream == base {
+        return Outcome::UpToDate;
+    }
+
+    let (Ok(base), Ok(local), Ok(upstream)) = (
+        std::str::from_utf8(base),
+        std::str::from_utf8(local),
+        std::str::from_utf8(upstream),
+    ) else {
+        return Outcome::Conflict(None);
+    };
+    match diffy::merge(base, local, upstream) {
+        Ok(merged) => Outcome::Merged(merged),
+        Err(conflicted) => Outcome::Conflict(Some(conflicted)),
+    }
+}
+
+#[derive(Default)]
+struct Upgrade {
+    updated: Vec<PathBuf>,
+    merged: Vec<PathBuf>,
+    conflicts: Vec<PathBuf>,
+    /// Binary files changed both locally and upstream, left as they are
+    binary_conflicts: Vec<PathBuf>,
+    no_base: Vec<String>,
+}
+
+impl Upgrade {
+    fn record(&mut self, outcome: &Outcome, path: &Path) {
+        let list = match outcome {
+            Outcome::Updated => &mut self.updated,
+            Outcome::Merged(_) => &mut self.merged,
+            Outcome::Conflict(Some(_)) => &mut self.conflicts,
+            Outcome::Conflict(None) => &mut self.binary_conflicts,
+            Outcome::UpToDate | Outcome::NoBase => return,
+        };
+        list.push(path.to_path_buf());
+    }
+
+    fn report(&self) -> String {
+        let mut report = String::new();
+        for (label, paths) in [("updated", &self.updated), ("merged", &self.merged)] {
+            for path in paths {
+                let _ = writeln!(report, "  {label}: {}", path.display());
+            }
+        }
+        for path in &self.conflicts {
+            let _ = writeln!(report, "  CONFLICT: {}", path.display());
+        }
+        for path in &self.binary_conflicts {
+            let _ = writeln!(
+                report,
+                "  CONFLICT (binary, local version kept): {}",
+                path.display()
+            );
+        }
+        for file in &self.no_base {
+            let _ = writeln!(
+                report,
+                "  skipped (no recorded version, see `zahuyach theme diff`): {file}"
+            );
+        }
+
+        let _ = write!(
+            report,
+            "Theme upgraded to {}: {} updated, {} merged, {} conflict(s)",
+            env!("CARGO_PKG_VERSION"),
+            self.updated.len(),
+            self.merged.len(),
+            self.conflicts.len() + self.binary_conflicts.len()
+        );
+        if !self.conflicts.is_empty() {
+            report.push_str("\nResolve the <<<<<<< / >>>>>>> markers in the conflicting files");
+        }
+        if !self.binary_conflicts.is_empty() {
+            report.push_str(
+                "\nReplace the binary files with the built-in versions (`zahuyach theme eject <file> --force`) \
+                 or keep yours; they are reported again on the next upgrade",
+            );
+        }
+        report
+    }
+}
+
+fn embedded(theme_file: &str) -> Result<Vec<u8>> {
+    BasicTemplate::get(theme_file)
+        .map(|file| file.data.into_owned())
+        .ok_or_else(|| {
+            ZahuyachError::InvalidInput(format!("Failed to get embedded file: {theme_file}"))
+        })
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project() -> (TempDir, Project) {
+        let temp_dir = TempDir::new().unwrap();
+        templates::copy_basic_template(temp_dir.path()).unwrap();
+        let project = Project::load(temp_dir.path()).unwrap();
+        (temp_dir, project)
+    }
+
+    #[test]
+    fn test_merge_outcomes() {
+        let base = b"a\nb\nc\n";
+
+        assert!(matches!(
+            merge(Some(base), base, b"a\nB\nc\n"),
+            Outcome::Updated
+        ));
+        assert!(matches!(
+            merge(Some(base), b"A\nb\nc\n", base),
+            Outcome::UpToDate
+        ));
+        assert!(matches!(
+            merge(Some(base), b"A\nb\nc\n", b"a\nb\nC\n"),
+            Outcome::Merged(merged) if merged == "A\nb\nC\n"
+        ));
+        assert!(matches!(
+            merge(Some(base), b"a\nX\nc\n", b"a\nY\nc\n"),
+            Outcome::Conflict(Some(merged)) if merged.contains("<<<<<<<")
+        ));
+        assert!(matches!(merge(None, b"x\n", b"y\n"), Outcome::NoBase));
+    }
+
+    #[test]
+    fn test_eject_single_file() {
+        let (_temp_dir, project) = project();
+        let post = project.templates_dir.join("post.html");
+        fs::write(&post, "custom").unwrap();
+
+        let report = project.eject(Some("post"), false).unwrap();
+        assert!(report.contains("skipped 1"));
+        assert_eq!(fs::read_to_string(&post).unwrap(), "custom");
+
+        project.eject(Some("templates/post.html"), true).unwrap();
+        assert_eq!(
+            fs::read(&post).unwrap(),
+            embedded("templates/post.html").unwrap()
+        );
+
+        assert!(project.eject(Some("missing.html"), false).is_err());
+    }
+
+    #[test]
+    fn test_diff_and_upgrade() {
+        let (_temp_dir, project) = project();
+        assert_eq!(
+            project.diff(None).unwrap(),
+            "Local theme files match the built-in theme"
+        );
+
+        // Локальная правка поверх старой версии, в которой нет последней строки
+        let theme_file = "templates/404.html";
+        let upstream = String::from_utf8(embedded(theme_file).unwrap()).unwrap();
+        let (old, last_line) = upstream.trim_end().rsplit_once('\n').unwrap();
+        let old = format!("{old}\n");
+        fs::write(project.base_path(theme_file), &old).unwrap();
+        let local_path = project.local_path(theme_file).unwrap();
+        fs::write(&local_path, format!("{{{{! local }}}}\n{old}")).unwrap();
+
+        let diff = project.diff(Some("404.html")).unwrap();
+        assert!(diff.contains("+{{! local }}"));
+
+        let report = project.upgrade().unwrap();
+        assert!(report.contains("1 merged, 0 conflict(s)"), "{report}");
+        let merged = fs::read_to_string(&local_path).unwrap();
+        assert!(merged.starts_with("{{! local }}\n"));
+        assert!(merged.contains(last_line));
+        assert_eq!(
+            fs::read_to_string(project.base_path(theme_file)).unwrap(),
+            upstream
+        );
+    }
+
+    #[test]
+    fn test_binary_conflict_keeps_base() {
+        let (_temp_dir, project) = project();
+        let theme_file = "static/favicon.svg";
+        let local_path = project.local_path(theme_file).unwrap();
+        let base_path = project.base_path(theme_file);
+        fs::write(&base_path, "old").unwrap();
+        fs::write(&local_path, [0xff, 0xfe, 0x00]).unwrap();
+
+        // Изменение темы не теряется: конфликт сообщается при каждом обновлении
+        for _ in 0..2 {
+            let report = project.upgrade().unwrap();
+            assert!(
+                report.contains("CONFLICT (binary, local version kept)"),
+                "{report}"
+            );
+            assert!(report.contains("1 conflict(s)"), "{report}");
+            assert_eq!(fs::read(&local_path).unwrap(), [0xff, 0xfe, 0x00]);
+            assert_eq!(fs::read_to_string(&base_path).unwrap(), "old");
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use crate::error::{Result, ZahuyachError};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
@@ -15,6 +16,14 @@ pub struct Config {
     pub date_format: Option<DateFormatConfig>,
     pub taxonomy: Option<TaxonomyConfig>,
     pub display: Option<DisplayConfig>,
+    /// Default post templates per content subdirectory (`"talks" = "talk"`)
+    pub layouts: Option<HashMap<String, String>>,
+    /// Author profiles keyed by the id used in front matter
+    pub authors: Option<HashMap<String, AuthorConfig>>,
+    pub images: Option<ImagesConfig>,
+    /// Languages of a multilingual site with their `[site]` overrides; the
+    /// site language is the default one
+    pub languages: Option<BTreeMap<String, LanguageConfig>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -41,6 +50,16 @@ pub struct BuildConfig {
     pub generate_sitemap: Option<bool>,
     pub clean_urls: Option<bool>,
     pub include_drafts: Option<bool>,
+    pub data_dir: Option<String>,
+    /// Theme from `themes_dir/<theme>/`; project templates and static files
+    /// override its files
+    pub theme: Option<String>,
+    pub themes_dir: Option<String>,
+    /// Directory with `<lang>.toml` translations of theme strings
+    pub i18n_dir: Option<String>,
+    /// Fail the build on missing template variables instead of rendering
+    /// them as empty
+    pub strict_templates: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -51,6 +70,7 @@ pub struct MarkdownConfig {
     pub enable_tasklists: Option<bool>,
     pub enable_smart_punctuation: Option<bool>,
     pub enable_heading_attributes: Option<bool>,
+    pub enable_math: Option<bool>,
     pub syntax_highlighting: Option<bool>,
     pub syntax_theme: Option<String>,
 }
@@ -59,11 +79,22 @@ pub struct MarkdownConfig {
 pub struct ContentConfig {
     pub enable_smart_quotes: Option<bool>,
     pub enable_emoji: Option<bool>,
+    pub emoji_images: Option<bool>,
+    pub emoji_image_url: Option<String>,
     pub external_links_new_tab: Option<bool>,
+    pub external_links_nofollow: Option<bool>,
+    pub external_links_ugc: Option<bool>,
+    pub external_links_class: Option<String>,
+    /// Render standalone images with a title as `<figure>` with a caption
+    pub image_figures: Option<bool>,
     pub add_anchor_links: Option<bool>,
+    pub toc_min_level: Option<u8>,
+    pub toc_max_level: Option<u8>,
     pub auto_excerpt: Option<bool>,
     pub excerpt_length: Option<usize>,
     pub excerpt_separator: Option<String>,
+    /// Character budget of the plain-text summary used as default description
+    pub summary_length: Option<usize>,
     pub reading_speed: Option<usize>, // words per minute
 }
 
@@ -109,6 +140,7 @@ pub struct DateFormatConfig {
 pub struct TaxonomyConfig {
     pub enable_tags: Option<bool>,
     pub enable_categories: Option<bool>,
+    pub enable_series: Option<bool>,
     pub min_tag_count: Option<usize>,
     pub max_tags_in_cloud: Option<usize>,
 }
@@ -120,6 +152,47 @@ pub struct DisplayConfig {
     pub related_posts_limit: Option<usize>,
 }
 
+/// Responsive image processing settings.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ImagesConfig {
+    pub enabled: Option<bool>,
+    /// Widths (in pixels) of the generated variants
+    pub widths: Option<Vec<u32>>,
+    /// Extra formats to generate besides the original one: `webp`, `avif`.
+    /// WebP is lossless; variants heavier than the original format at the
+    /// same width are dropped
+    pub formats: Option<Vec<String>>,
+    /// Encoding quality for lossy formats (JPEG, AVIF), 1-100
+    pub quality: Option<u8>,
+    /// Value of the `sizes` attribute
+    pub sizes: Option<String>,
+    /// Where processed images are kept between builds
+    pub cache_dir: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AuthorConfig {
+    pub name: String,
+    pub title: Option<String>,
+    pub bio: Option<String>,
+    pub avatar: Option<String>,
+    pub email: Option<String>,
+    pub url: Option<String>,
+    pub social: Option<SocialConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LanguageConfig {
+    /// Name shown in language switchers, e.g. "English"
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub timezone: Option<String>,
+    pub email: Option<String>,
+    pub social: Option<SocialConfig>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SocialConfig {
     pub github: Option<String>,
@@ -155,6 +228,22 @@ impl Config {
             .unwrap_or(3) // 3 paragraphs by default
     }
 
+    #[must_use]
+    pub fn is_auto_excerpt_enabled(&self) -> bool {
+        self.content
+            .as_ref()
+            .and_then(|c| c.auto_excerpt)
+            .unwrap_or(true)
+    }
+
+    #[must_use]
+    pub fn get_summary_length(&self) -> usize {
+        self.content
+            .as_ref()
+            .and_then(|c| c.summary_length)
+            .unwrap_or(160)
+    }
+
     pub fn get_reading_speed(&self) -> usize {
         self.content
             .as_ref()
@@ -200,6 +289,126 @@ impl Config {
         self.build.include_drafts.unwrap_or(false)
     }
 
+    #[must_use]
+    pub fn get_data_dir(&self) -> &str {
+        self.build.data_dir.as_deref().unwrap_or("data")
+    }
+
+    #[must_use]
+    pub fn get_themes_dir(&self) -> &str {
+        self.build.themes_dir.as_deref().unwrap_or("themes")
+    }
+
+    #[must_use]
+    pub fn get_i18n_dir(&self) -> &str {
+        self.build.i18n_dir.as_deref().unwrap_or("i18n")
+    }
+
+    #[must_use]
+    pub fn is_strict_templates(&self) -> bool {
+        self.build.strict_templates.unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn get_language(&self) -> &str {
+        self.site.language.as_deref().unwrap_or("ru")
+    }
+
+    /// Returns the site languages, the default one first.
+    #[must_use]
+    pub fn get_languages(&self) -> Vec<&str> {
+        let default = self.get_language();
+        let mut languages = vec![default];
+        languages.extend(
+            self.languages
+                .iter()
+                .flat_map(BTreeMap::keys)
+                .map(String::as_str)
+                .filter(|lang| *lang != default),
+        );
+        languages
+    }
+
+    #[must_use]
+    pub fn is_multilingual(&self) -> bool {
+        self.get_languages().len() > 1
+    }
+
+    /// Returns the display name of `lang`, or the code itself.
+    #[must_use]
+    pub fn get_language_name<'a>(&'a self, lang: &'a str) -> &'a str {
+        self.languages
+            .as_ref()
+            .and_then(|languages| languages.get(lang))
+            .and_then(|language| language.name.as_deref())
+            .unwrap_or(lang)
+    }
+
+    /// Returns `[site]` with the overrides from `[languages.<lang>]`.
+    #[must_use]
+    pub fn site_for_language(&self, lang: &str) -> SiteConfig {
+        let mut site = self.site.clone();
+        site.language = Some(lang.to_string());
+
+        let Some(language) = self.languages.as_ref().and_then(|l| l.get(lang)) else {
+            return site;
+        };
+        if let Some(title) = &language.title {
+            site.title.clone_from(title);
+        }
+        if let Some(description) = &language.description {
+            site.description.clone_from(description);
+        }
+        if let Some(author) = &language.author {
+            site.author.clone_from(author);
+        }
+        if language.timezone.is_some() {
+            site.timezone.clone_from(&language.timezone);
+        }
+        if language.email.is_some() {
+            site.email.clone_from(&language.email);
+        }
+        if language.social.is_some() {
+            site.social.clone_from(&language.social);
+        }
+        site
+    }
+
+    /// Returns the directory of the selected theme, if any.
+    #[must_use]
+    pub fn get_theme_dir(&self) -> Option<PathBuf> {
+        self.build
+            .theme
+            .as_deref()
+            .map(|theme| Path::new(self.get_themes_dir()).join(theme))
+    }
+
+    #[must_use]
+    pub fn is_image_processing_enabled(&self) -> bool {
+        self.images
+            .as_ref()
+            .and_then(|i| i.enabled)
+            .unwrap_or(false)
+    }
+
+    #[must_use]
+    pub fn is_code_copy_enabled(&self) -> bool {
+        self.features
+            .as_ref()
+            .and_then(|f| f.code_copy)
+            .unwrap_or(true)
+    }
+
+    /// Whether themes render the `seo` context: canonical link, Open Graph,
+    /// Twitter card and JSON-LD.
+    #[must_use]
+    pub fn is_seo_enabled(&self) -> bool {
+        self.features
+            .as_ref()
+            .and_then(|f| f.seo_optimized)
+            .unwrap_or(false)
+    }
+
     pub fn get_max_tags_in_cloud(&self) -> usize {
         self.taxonomy
             .as_ref()
@@ -228,6 +437,14 @@ impl Config {
             .unwrap_or(false)
     }
 
+    #[must_use]
+    pub fn is_series_enabled(&self) -> bool {
+        self.taxonomy
+            .as_ref()
+            .and_then(|t| t.enable_series)
+            .unwrap_or(true)
+    }
+
     pub fn get_popular_tags_limit(&self) -> usize {
         self.display
             .as_ref()
@@ -248,6 +465,28 @@ impl Config {
             .and_then(|d| d.related_posts_limit)
             .unwrap_or(3)
     }
+
+    /// Looks up an author profile by its id.
+    #[must_use]
+    pub fn get_author(&self, id: &str) -> Option<&AuthorConfig> {
+        self.authors.as_ref()?.get(id)
+    }
+
+    /// Returns the default template for a post located at `relative_path`
+    /// inside the content directory. The deepest matching directory wins.
+    #[must_use]
+    pub fn get_directory_layout(&self, relative_path: &Path) -> Option<&str> {
+        let layouts = self.layouts.as_ref()?;
+
+        relative_path
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .find_map(|dir| {
+                let key = dir.to_string_lossy().replace('\\', "/");
+                layouts.get(&key).map(String::as_str)
+            })
+    }
 }
 
 #[cfg(test)]
@@ -278,6 +517,11 @@ mod tests {
                 generate_sitemap: None,
                 clean_urls: None,
                 include_drafts: None,
+                data_dir: None,
+                theme: None,
+                themes_dir: None,
+                i18n_dir: None,
+                strict_templates: None,
             },
             markdown: None,
             content: None,
@@ -287,6 +531,10 @@ mod tests {
             date_format: None,
             taxonomy: None,
             display: None,
+            layouts: None,
+            authors: None,
+            images: None,
+            languages: None,
         };
 
         assert_eq!(config.get_excerpt_separator(), "<!-- more -->");
@@ -298,6 +546,17 @@ mod tests {
         assert!(config.is_rss_enabled());
         assert!(config.should_clean_output());
         assert!(!config.include_drafts());
+        assert!(config.is_series_enabled());
+        assert_eq!(config.get_data_dir(), "data");
+        assert_eq!(config.get_themes_dir(), "themes");
+        assert_eq!(config.get_theme_dir(), None);
+        assert!(config.get_author("test").is_none());
+        assert_eq!(config.get_directory_layout(Path::new("talks/x.md")), None);
+        assert!(!config.is_image_processing_enabled());
+        assert_eq!(config.get_languages(), vec!["ru"]);
+        assert!(!config.is_multilingual());
+        assert!(!config.is_strict_templates());
+        assert!(!config.is_seo_enabled());
     }
 
     #[test]
@@ -329,6 +588,7 @@ rss_limit = 30
 generate_sitemap = true
 clean_urls = false
 include_drafts = true
+data_dir = "site-data"
 
 [markdown]
 enable_tables = true
@@ -337,14 +597,22 @@ enable_strikethrough = true
 enable_tasklists = true
 enable_smart_punctuation = false
 enable_heading_attributes = true
+enable_math = true
 syntax_highlighting = true
 syntax_theme = "monokai"
 
 [content]
 enable_smart_quotes = false
 enable_emoji = true
+emoji_images = true
+emoji_image_url = "/static/emoji/{code}.png"
 external_links_new_tab = false
+external_links_nofollow = true
+external_links_class = "external"
+image_figures = true
 add_anchor_links = true
+toc_min_level = 1
+toc_max_level = 4
 auto_excerpt = false
 excerpt_length = 5
 excerpt_separator = "<!--break-->"
@@ -375,6 +643,7 @@ full = "%d/%m/%Y %H:%M"
 [taxonomy]
 enable_tags = false
 enable_categories = true
+enable_series = false
 min_tag_count = 2
 max_tags_in_cloud = 100
 
@@ -382,6 +651,31 @@ max_tags_in_cloud = 100
 popular_tags_limit = 30
 recent_posts_limit = 10
 related_posts_limit = 5
+
+[authors.ivan]
+name = "Ivan Ivanov"
+bio = "Rust developer"
+avatar = "/static/ivan.png"
+
+[authors.ivan.social]
+github = "https://github.com/ivan"
+
+[layouts]
+talks = "talk"
+"talks/keynotes" = "keynote.html"
+
+[images]
+enabled = true
+widths = [320, 640]
+formats = ["webp"]
+
+[languages.en]
+name = "English"
+
+[languages.de]
+name = "Deutsch"
+title = "Testblog"
+email = "de@test.com"
 "#;
 
         let config: Config = toml::from_str(toml_str).unwrap();
@@ -422,6 +716,17 @@ related_posts_limit = 5
         assert_eq!(content.enable_smart_quotes, Some(false));
         assert_eq!(content.excerpt_separator.as_ref().unwrap(), "<!--break-->");
         assert_eq!(content.reading_speed, Some(250));
+        assert_eq!(content.emoji_images, Some(true));
+        assert_eq!(
+            content.emoji_image_url.as_deref(),
+            Some("/static/emoji/{code}.png")
+        );
+        assert_eq!(content.external_links_nofollow, Some(true));
+        assert_eq!(content.external_links_ugc, None);
+        assert_eq!(content.external_links_class.as_deref(), Some("external"));
+        assert_eq!(content.image_figures, Some(true));
+        assert_eq!(content.toc_min_level, Some(1));
+        assert_eq!(content.toc_max_level, Some(4));
 
         // Test RSS config
         let rss = config.rss.as_ref().unwrap();
@@ -439,10 +744,45 @@ related_posts_limit = 5
         assert!(!config.is_rss_enabled());
         assert!(!config.should_clean_output());
         assert!(config.include_drafts());
+        assert_eq!(config.get_data_dir(), "site-data");
         assert_eq!(config.get_popular_tags_limit(), 30);
         assert_eq!(config.get_recent_posts_limit(), 10);
         assert_eq!(config.get_max_tags_in_cloud(), 100);
         assert!(!config.is_tags_enabled());
         assert!(config.is_categories_enabled());
+        assert!(!config.is_series_enabled());
+        assert!(config.is_image_processing_enabled());
+
+        // Test authors
+        let ivan = config.get_author("ivan").unwrap();
+        assert_eq!(ivan.name, "Ivan Ivanov");
+        assert_eq!(ivan.bio.as_deref(), Some("Rust developer"));
+        assert_eq!(
+            ivan.social.as_ref().unwrap().github.as_deref(),
+            Some("https://github.com/ivan")
+        );
+        assert!(config.get_author("petr").is_none());
+
+        // Test directory layouts
+        assert_eq!(
+            config.get_directory_layout(Path::new("talks/rustconf.md")),
+            Some("talk")
+        );
+        assert_eq!(
+            config.get_directory_layout(Path::new("talks/keynotes/opening/index.md")),
+            Some("keynote.html")
+        );
+        assert_eq!(config.get_directory_layout(Path::new("hello.md")), None);
+
+        // Test languages
+        assert_eq!(config.get_languages(), vec!["en", "de"]);
+        assert!(config.is_multilingual());
+        assert_eq!(config.get_language_name("de"), "Deutsch");
+        assert_eq!(config.get_language_name("fr"), "fr");
+        let german = config.site_for_language("de");
+        assert_eq!(german.title, "Testblog");
+        assert_eq!(german.description, "A test blog");
+        assert_eq!(german.email.as_deref(), Some("de@test.com"));
+        assert_eq!(german.language.as_deref(), Some("de"));
     }
 }
@@ -1,8 +1,18 @@
 use crate::error::{Result, ZahuyachError};
-use pulldown_cmark::{Parser, html};
+use crate::helpers;
+use crate::markdown::{self, RenderOptions, TocEntry};
+use crate::shortcodes::{self, Expanded, ShortcodeRenderer, Source};
+use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// File name that turns a content directory into a page bundle.
+pub const BUNDLE_INDEX: &str = "index.md";
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Post {
@@ -10,7 +20,21 @@ pub struct Post {
     pub content: String,
     pub html_content: String,
     pub slug: String,
+    /// Site path of the post, `/posts/<slug>` or `/<lang>/posts/<slug>`
+    pub url: String,
     pub file_path: PathBuf,
+    /// Directory of the page bundle, if the post is `<dir>/index.md`
+    pub bundle_dir: Option<PathBuf>,
+    /// Bundle files relative to `bundle_dir` (images, attachments, ...)
+    pub assets: Vec<PathBuf>,
+    /// Table of contents built from the post headings
+    pub toc: Vec<TocEntry>,
+    /// HTML of the post excerpt, empty when there is none
+    pub excerpt: String,
+    /// Plain-text summary, the default description
+    pub summary: String,
+    /// Problems found while rendering (e.g. invalid TeX), with file and line
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -18,16 +42,81 @@ pub struct FrontMatter {
     pub title: String,
     pub date: String,
     pub author: Option<String>,
+    /// Ids (or names) of all post authors, overrides `author`
+    pub authors: Option<Vec<String>>,
     pub tags: Option<Vec<String>>,
     pub categories: Option<Vec<String>>,
     pub description: Option<String>,
     pub draft: Option<bool>,
     pub featured: Option<bool>,
+    /// Name of the multi-part series the post belongs to
+    pub series: Option<String>,
+    /// Position of the post within its series
+    pub series_order: Option<usize>,
+    /// Template used to render the post instead of `post`
+    #[serde(alias = "layout")]
+    pub template: Option<String>,
+    /// Language of the post content, overriding `site.language`
+    pub lang: Option<String>,
+    /// Id shared by translations of the same post in multilingual sites
+    pub translation_key: Option<String>,
+    /// Cover image for social previews; relative paths point into the
+    /// page bundle
+    pub image: Option<String>,
+    /// Any front matter keys not covered by the fields above
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl FrontMatter {
+    /// Returns a custom front matter value converted to `T`.
+    ///
+    /// Returns `None` if the key is missing or its value can't be
+    /// represented as `T`.
+    #[must_use]
+    pub fn extra<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.extra
+            .get(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
 }
 
 impl Post {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let path = path.as_ref();
+        Self::from_file_with_options(path, &RenderOptions::default())
+    }
+
+    /// Loads a post, rendering its Markdown with the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or its front matter is invalid.
+    pub fn from_file_with_options<P: AsRef<Path>>(
+        path: P,
+        options: &RenderOptions,
+    ) -> Result<Self> {
+        Self::load(path.as_ref(), options, None)
+    }
+
+    /// Loads a post, expanding `{{< shortcode >}}` tags with `shortcodes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read, its front matter is
+    /// invalid or it uses an unknown or malformed shortcode.
+    pub fn from_file_with_shortcodes<P: AsRef<Path>>(
+        path: P,
+        options: &RenderOptions,
+        shortcodes: &dyn ShortcodeRenderer,
+    ) -> Result<Self> {
+        Self::load(path.as_ref(), options, Some(shortcodes))
+    }
+
+    fn load(
+        path: &Path,
+        options: &RenderOptions,
+        shortcodes: Option<&dyn ShortcodeRenderer>,
+    ) -> Result<Self> {
         let content = fs::read_to_string(path)?;
 
         // Разделяем front matter и содержимое
@@ -38,27 +127,174 @@ impl Post {
             ZahuyachError::InvalidInput(format!("Failed to parse front matter: {}", e))
         })?;
 
-        // Конвертируем Markdown в HTML
-        let parser = Parser::new(&markdown_content);
-        let mut html_content = String::new();
-        html::push_html(&mut html_content, parser);
-
-        // Создаем slug из имени файла
-        let slug = path
-            .file_stem()
+        // Для page bundle (`my-post/index.md`) slug берется из имени директории
+        let bundle_dir = Self::bundle_dir_of(path);
+        let slug = bundle_dir
+            .as_ref()
+            .map_or_else(|| path.file_stem(), |dir| dir.file_name())
             .and_then(|s| s.to_str())
             .unwrap_or("untitled")
             .to_string();
 
+        let assets = match &bundle_dir {
+            Some(dir) => Self::collect_bundle_assets(dir)?,
+            None => Vec::new(),
+        };
+        // Ссылки на ресурсы бандла учитывают путь из base_url (`/blog`)
+        let assets_url = format!("{}/posts/{slug}", helpers::base_path(&options.base_url));
+
+        // Типографика зависит от языка поста
+        let options = &front_matter
+            .lang
+            .as_deref()
+            .map_or_else(|| options.clone(), |lang| options.with_language(lang));
+
+        // Markdown начинается на строке с закрывающим `---`
+        let first_line = front_matter_str.matches('\n').count() + 1;
+        let mut warnings = Vec::new();
+
+        // Шорткоды заменяются плейсхолдерами до разбора Markdown
+        let expanded = match shortcodes {
+            Some(renderer) => shortcodes::expand(
+                &markdown_content,
+                Source { path, first_line },
+                renderer,
+                &|inner| markdown::render(markdown::parser(inner, options), options).html,
+            )?,
+            None => Expanded::unchanged(markdown_content.clone()),
+        };
+
+        let line_at =
+            |offset: usize| first_line + expanded.markdown[..offset].matches('\n').count();
+        // Картинка, для которой пока не встретился alt: адрес и строка
+        let mut image_without_alt: Option<(String, usize)> = None;
+
+        // Конвертируем Markdown в HTML
+        let parser = markdown::parser(&expanded.markdown, options)
+            .into_offset_iter()
+            .map(|(event, range)| {
+                let event = markdown::render_math(event).unwrap_or_else(|error| {
+                    warnings.push(format!(
+                        "Invalid TeX in '{}' at line {}: {}",
+                        path.display(),
+                        line_at(range.start),
+                        error.message
+                    ));
+                    error.fallback
+                });
+                match &event {
+                    Event::Start(Tag::Image { dest_url, .. }) => {
+                        image_without_alt = Some((dest_url.to_string(), line_at(range.start)));
+                    }
+                    Event::Text(text) | Event::Code(text) if !text.trim().is_empty() => {
+                        image_without_alt = None;
+                    }
+                    Event::End(TagEnd::Image) => {
+                        if let Some((src, line)) = image_without_alt.take() {
+                            warnings.push(format!(
+                                "Image '{src}' has no alt text in '{}' at line {line}",
+                                path.display()
+                            ));
+                        }
+                    }
+                    _ => {}
+                }
+                match &bundle_dir {
+                    Some(dir) => Self::rewrite_bundle_link(event, dir, &assets_url),
+                    None => event,
+                }
+            });
+        let rendered = markdown::render(parser, options);
+
         Ok(Post {
             front_matter,
             content: markdown_content,
-            html_content,
+            html_content: expanded.inject(&rendered.html),
+            url: format!("/posts/{slug}"),
             slug,
             file_path: path.to_path_buf(),
+            bundle_dir,
+            assets,
+            toc: rendered.toc,
+            excerpt: expanded.inject(&rendered.excerpt),
+            summary: rendered.summary,
+            warnings,
         })
     }
 
+    /// Returns the bundle directory if `path` is the `index.md` of a page bundle.
+    fn bundle_dir_of(path: &Path) -> Option<PathBuf> {
+        if path.file_name()? != BUNDLE_INDEX {
+            return None;
+        }
+        path.parent()
+            .filter(|dir| dir.file_name().is_some())
+            .map(Path::to_path_buf)
+    }
+
+    /// Collects every file of the bundle except its `index.md`.
+    fn collect_bundle_assets(dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut assets = Vec::new();
+
+        for entry in WalkDir::new(dir).min_depth(1).sort_by_file_name() {
+            let entry = entry.map_err(|e| ZahuyachError::Io(e.into()))?;
+            let path = entry.path();
+
+            if !entry.file_type().is_file() || path == dir.join(BUNDLE_INDEX) {
+                continue;
+            }
+
+            if let Ok(relative) = path.strip_prefix(dir) {
+                assets.push(relative.to_path_buf());
+            }
+        }
+
+        Ok(assets)
+    }
+
+    /// Rewrites relative image and link targets that point into the bundle
+    /// to `assets_url`, where the bundle assets are published.
+    fn rewrite_bundle_link<'a>(event: Event<'a>, dir: &Path, assets_url: &str) -> Event<'a> {
+        let rewrite = |dest: CowStr<'a>| -> CowStr<'a> {
+            let file = dest.split(['#', '?']).next().unwrap_or_default();
+            let is_relative = !file.is_empty() && !file.starts_with('/') && !file.contains(':');
+
+            if is_relative && dir.join(file).is_file() {
+                let file = file.strip_prefix("./").unwrap_or(file);
+                let suffix = &dest[dest.find(['#', '?']).unwrap_or(dest.len())..];
+                format!("{assets_url}/{file}{suffix}").into()
+            } else {
+                dest
+            }
+        };
+
+        match event {
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Image {
+                link_type,
+                dest_url: rewrite(dest_url),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: rewrite(dest_url),
+                title,
+                id,
+            }),
+            other => other,
+        }
+    }
+
     fn parse_front_matter(content: &str) -> Result<(String, String)> {
         if !content.starts_with("---") {
             return Err(ZahuyachError::InvalidInput(
@@ -83,4 +319,263 @@ impl Post {
     pub fn is_draft(&self) -> bool {
         self.front_matter.draft.unwrap_or(false)
     }
+
+    #[must_use]
+    pub const fn is_bundle(&self) -> bool {
+        self.bundle_dir.is_some()
+    }
+
+    /// Returns the front matter description, falling back to the summary
+    /// and then to the title.
+    #[must_use]
+    pub fn description(&self) -> &str {
+        self.front_matter
+            .description
+            .as_deref()
+            .filter(|d| !d.trim().is_empty())
+            .or_else(|| Some(self.summary.as_str()).filter(|s| !s.is_empty()))
+            .unwrap_or(&self.front_matter.title)
+    }
+
+    /// Returns the site path (or external URL) of the front matter `image`.
+    /// Relative paths are resolved against the page bundle, or against the
+    /// site root for single-file posts.
+    #[must_use]
+    pub fn image(&self) -> Option<String> {
+        let image = self
+            .front_matter
+            .image
+            .as_deref()
+            .map(str::trim)
+            .filter(|image| !image.is_empty())?;
+
+        if image.contains("://") || image.starts_with('/') {
+            return Some(image.to_string());
+        }
+        let image = image.trim_start_matches("./");
+        Some(if self.bundle_dir.is_some() {
+            format!("{}/{image}", self.url)
+        } else {
+            format!("/{image}")
+        })
+    }
+
+    /// Publishes the post under `prefix` (e.g. `/en` for a translation),
+    /// moving links to its bundle files along with it. `base_path` is the
+    /// path part of the site base URL the links start with.
+    pub fn relocate(&mut self, prefix: &str, base_path: &str) {
+        let url = format!("{prefix}/posts/{}", self.slug);
+        if self.bundle_dir.is_some() {
+            let from = format!("\"{base_path}{}/", self.url);
+            let to = format!("\"{base_path}{url}/");
+            self.html_content = self.html_content.replace(&from, &to);
+            self.excerpt = self.excerpt.replace(&from, &to);
+        }
+        self.url = url;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const POST: &str = "---\ntitle: \"Bundle\"\ndate: \"2024-01-01\"\nimage: ./diagram.png\n---\n\n![Diagram](diagram.png \"Flow\")\n\n[Download](./files/data.csv#top) [Missing](missing.png) [Site](https://example.com)\n";
+
+    #[test]
+    fn test_bundle_post_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let bundle = temp_dir.path().join("my-post");
+        fs::create_dir_all(bundle.join("files")).unwrap();
+        fs::write(bundle.join(BUNDLE_INDEX), POST).unwrap();
+        fs::write(bundle.join("diagram.png"), b"png").unwrap();
+        fs::write(bundle.join("files/data.csv"), b"a,b").unwrap();
+
+        let post = Post::from_file(bundle.join(BUNDLE_INDEX)).unwrap();
+
+        assert!(post.is_bundle());
+        assert_eq!(post.slug, "my-post");
+        assert_eq!(
+            post.assets,
+            vec![
+                PathBuf::from("diagram.png"),
+                PathBuf::from("files/data.csv")
+            ]
+        );
+        assert!(
+            post.html_content
+                .contains(r#"src="/posts/my-post/diagram.png""#)
+        );
+        assert!(
+            post.html_content
+                .contains(r#"href="/posts/my-post/files/data.csv#top""#)
+        );
+        assert!(post.html_content.contains(r#"href="missing.png""#));
+        assert!(post.html_content.contains(r#"href="https://example.com""#));
+        assert_eq!(post.image().as_deref(), Some("/posts/my-post/diagram.png"));
+
+        let mut post = post;
+        post.relocate("/en", "");
+        assert_eq!(post.url, "/en/posts/my-post");
+        assert_eq!(
+            post.image().as_deref(),
+            Some("/en/posts/my-post/diagram.png")
+        );
+        assert!(
+            post.html_content
+                .contains(r#"src="/en/posts/my-post/diagram.png""#)
+        );
+    }
+
+    #[test]
+    fn test_bundle_links_under_base_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let bundle = temp_dir.path().join("my-post");
+        fs::create_dir_all(&bundle).unwrap();
+        fs::write(bundle.join(BUNDLE_INDEX), POST).unwrap();
+        fs::write(bundle.join("diagram.png"), b"png").unwrap();
+        let options = RenderOptions {
+            base_url: "https://example.com/blog".to_string(),
+            ..RenderOptions::default()
+        };
+
+        let mut post = Post::from_file_with_options(bundle.join(BUNDLE_INDEX), &options).unwrap();
+        assert!(
+            post.html_content
+                .contains(r#"src="/blog/posts/my-post/diagram.png""#)
+        );
+
+        post.relocate("/en", "/blog");
+        assert!(
+            post.html_content
+                .contains(r#"src="/blog/en/posts/my-post/diagram.png""#)
+        );
+    }
+
+    #[test]
+    fn test_standalone_post_keeps_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("standalone.md");
+        fs::write(&path, POST).unwrap();
+        fs::write(temp_dir.path().join("diagram.png"), b"png").unwrap();
+
+        let post = Post::from_file(&path).unwrap();
+
+        assert!(!post.is_bundle());
+        assert_eq!(post.slug, "standalone");
+        assert!(post.assets.is_empty());
+        assert!(post.html_content.contains(r#"src="diagram.png""#));
+        assert_eq!(post.image().as_deref(), Some("/diagram.png"));
+    }
+
+    #[test]
+    fn test_extra_front_matter() {
+        let front_matter: FrontMatter = serde_yaml::from_str(
+            "title: Extra\ndate: 2024-01-01\ncover: cover.png\nhero:\n  color: \"#ff0000\"\n  height: 320\n",
+        )
+        .unwrap();
+
+        assert_eq!(front_matter.title, "Extra");
+        assert!(!front_matter.extra.contains_key("title"));
+        assert_eq!(
+            front_matter.extra::<String>("cover").as_deref(),
+            Some("cover.png")
+        );
+        assert_eq!(
+            front_matter.extra::<Value>("hero"),
+            Some(serde_json::json!({"color": "#ff0000", "height": 320}))
+        );
+        assert_eq!(front_matter.extra::<u32>("cover"), None);
+        assert_eq!(front_matter.extra::<String>("missing"), None);
+    }
+
+    #[test]
+    fn test_layout_front_matter_alias() {
+        let front_matter: FrontMatter =
+            serde_yaml::from_str("title: Talk\ndate: 2024-01-01\nlayout: talk\n").unwrap();
+
+        assert_eq!(front_matter.template.as_deref(), Some("talk"));
+        assert!(front_matter.extra.is_empty());
+    }
+
+    #[test]
+    fn test_post_lang_overrides_typography() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("hello.md");
+        fs::write(
+            &path,
+            "---\ntitle: Hello\ndate: 2024-01-01\nlang: en\n---\n\nSay \"hi\"\n",
+        )
+        .unwrap();
+        let options = RenderOptions {
+            smart_quotes: true,
+            ..RenderOptions::default()
+        };
+
+        let post = Post::from_file_with_options(&path, &options).unwrap();
+
+        assert_eq!(post.front_matter.lang.as_deref(), Some("en"));
+        assert_eq!(post.html_content, "<p>Say “hi”</p>\n");
+    }
+
+    #[test]
+    fn test_math_rendering_and_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("math.md");
+        fs::write(
+            &path,
+            "---\ntitle: Math\ndate: 2024-01-01\n---\n\nEuler: $e^{i\\pi} + 1 = 0$\n\n$$\n\\frac{1}{\\oops}\n$$\n",
+        )
+        .unwrap();
+        let options = RenderOptions {
+            math: true,
+            ..RenderOptions::default()
+        };
+
+        let post = Post::from_file_with_options(&path, &options).unwrap();
+
+        assert!(post.html_content.contains("<math"));
+        assert!(post.html_content.contains("<msup><mi>e</mi>"));
+        assert!(post.html_content.contains(r#"<code class="math-error""#));
+        assert_eq!(post.warnings.len(), 1);
+        assert!(post.warnings[0].contains("math.md' at line 8"));
+        assert!(post.warnings[0].contains(r"Unknown command '\oops'"));
+    }
+
+    #[test]
+    fn test_missing_alt_text_warning() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("images.md");
+        fs::write(
+            &path,
+            "---\ntitle: Images\ndate: 2024-01-01\n---\n\n![Diagram](a.png)\n\n![](b.png)\n",
+        )
+        .unwrap();
+
+        let post = Post::from_file(&path).unwrap();
+
+        assert_eq!(post.warnings.len(), 1);
+        assert!(
+            post.warnings[0].starts_with("Image 'b.png' has no alt text in '")
+                && post.warnings[0].ends_with("images.md' at line 8")
+        );
+    }
+
+    #[test]
+    fn test_excerpt_and_description() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("excerpt.md");
+        fs::write(
+            &path,
+            "---\ntitle: Excerpt\ndate: 2024-01-01\n---\n\nShort *intro*. <!-- more --> Details.\n\nMore text.\n",
+        )
+        .unwrap();
+
+        let post = Post::from_file(&path).unwrap();
+
+        assert_eq!(post.excerpt, "<p>Short <em>intro</em>. </p>\n");
+        assert_eq!(post.summary, "Short intro.");
+        assert_eq!(post.description(), "Short intro.");
+        assert!(!post.html_content.contains("<!-- more -->"));
+    }
 }
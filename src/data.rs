@@ -0,0 +1,187 @@
+//! Global data files exposed to templates.
+//!
+//! Every `.toml`, `.yaml`/`.yml`, `.json` and `.csv` file found in the data
+//! directory becomes a key of the `data` object, named after the file stem.
+//! Subdirectories become nested objects, so `data/menus/main.toml` is
+//! available as `data.menus.main`.
+
+use crate::error::{Result, ZahuyachError};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Loads all data files from `dir` into a single JSON object.
+///
+/// A missing directory yields an empty object.
+///
+/// # Errors
+///
+/// Returns an error if a file can't be read or parsed, or if two files
+/// map to the same key (e.g. `links.toml` and `links.json`).
+pub fn load_data_dir(dir: &Path) -> Result<Value> {
+    if !dir.is_dir() {
+        return Ok(Value::Object(Map::new()));
+    }
+
+    load_dir(dir).map(Value::Object)
+}
+
+fn load_dir(dir: &Path) -> Result<Map<String, Value>> {
+    let mut data = Map::new();
+
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+
+    for entry in entries {
+        let path = entry.path();
+
+        let (key, value) = if path.is_dir() {
+            let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            (name.to_string(), Value::Object(load_dir(&path)?))
+        } else {
+            let Some(value) = load_file(&path)? else {
+                continue;
+            };
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            (stem.to_string(), value)
+        };
+
+        if data.contains_key(&key) {
+            return Err(ZahuyachError::InvalidInput(format!(
+                "Duplicate data key '{}' in '{}'",
+                key,
+                dir.display()
+            )));
+        }
+
+        data.insert(key, value);
+    }
+
+    Ok(data)
+}
+
+/// Parses a single data file. Returns `None` for unsupported extensions.
+fn load_file(path: &Path) -> Result<Option<Value>> {
+    let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+        return Ok(None);
+    };
+
+    let parse_error = |e: &dyn std::fmt::Display| {
+        ZahuyachError::InvalidInput(format!(
+            "Failed to parse data file '{}': {}",
+            path.display(),
+            e
+        ))
+    };
+
+    let value = match extension.to_lowercase().as_str() {
+        "toml" => {
+            let table: toml::Table =
+                toml::from_str(&fs::read_to_string(path)?).map_err(|e| parse_error(&e))?;
+            serde_json::to_value(table).map_err(|e| parse_error(&e))?
+        }
+        "yaml" | "yml" => {
+            serde_yaml::from_str(&fs::read_to_string(path)?).map_err(|e| parse_error(&e))?
+        }
+        "json" => serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| parse_error(&e))?,
+        "csv" => load_csv(path).map_err(|e| parse_error(&e))?,
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+/// Reads a CSV file with a header row into an array of objects.
+fn load_csv(path: &Path) -> csv::Result<Value> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+
+    let rows = reader
+        .records()
+        .map(|record| {
+            record.map(|record| {
+                let row: Map<String, Value> = headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(header, field)| (header.to_string(), Value::from(field)))
+                    .collect();
+                Value::Object(row)
+            })
+        })
+        .collect::<csv::Result<Vec<_>>>()?;
+
+    Ok(Value::Array(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_data_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("menus")).unwrap();
+        fs::write(dir.join("site.toml"), "name = \"Blog\"\nyear = 2024\n").unwrap();
+        fs::write(
+            dir.join("links.yaml"),
+            "- title: Rust\n  url: https://rust-lang.org\n",
+        )
+        .unwrap();
+        fs::write(dir.join("projects.json"), r#"[{"name": "zahuyach"}]"#).unwrap();
+        fs::write(
+            dir.join("talks.csv"),
+            "title,year\nIntro,2023\n\"Hello, world\",2024\n",
+        )
+        .unwrap();
+        fs::write(dir.join("menus/main.yml"), "home: /\n").unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let data = load_data_dir(dir).unwrap();
+
+        assert_eq!(
+            data,
+            json!({
+                "site": {"name": "Blog", "year": 2024},
+                "links": [{"title": "Rust", "url": "https://rust-lang.org"}],
+                "projects": [{"name": "zahuyach"}],
+                "talks": [
+                    {"title": "Intro", "year": "2023"},
+                    {"title": "Hello, world", "year": "2024"}
+                ],
+                "menus": {"main": {"home": "/"}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_missing_data_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = load_data_dir(&temp_dir.path().join("data")).unwrap();
+        assert_eq!(data, json!({}));
+    }
+
+    #[test]
+    fn test_duplicate_data_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("links.toml"), "a = 1\n").unwrap();
+        fs::write(temp_dir.path().join("links.json"), "{}").unwrap();
+
+        assert!(load_data_dir(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_data_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("broken.json"), "{").unwrap();
+
+        let error = load_data_dir(temp_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("broken.json"));
+    }
+}
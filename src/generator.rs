@@ -1,18 +1,55 @@
-use crate::config::Config;
-use crate::content::Post;
+use crate::config::{AuthorConfig, Config, SocialConfig};
+use crate::content::{BUNDLE_INDEX, FrontMatter, Post};
+use crate::data;
 use crate::error::{Result, ZahuyachError};
+use crate::helpers;
+use crate::i18n::Translations;
+use crate::images::{ImageProcessor, ImageSettings};
+use crate::markdown::{self, RenderOptions, TocEntry};
+use crate::shortcodes::{SHORTCODES_DIR, ShortcodeRenderer};
+use crate::templates;
 use chrono::Datelike;
-use handlebars::Handlebars;
+use handlebars::{Handlebars, RenderError, RenderErrorReason};
 use serde_json::{Value, json};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// Page types whose context [`SiteGenerator::page_context`] builds.
+pub const PAGE_TYPES: [&str; 13] = [
+    "post",
+    "index",
+    "archive",
+    "about",
+    "tags",
+    "tag",
+    "category",
+    "series_list",
+    "series",
+    "authors",
+    "author",
+    "rss",
+    "404",
+];
+
+/// Id of the registered author of the example posts, see
+/// [`SiteGenerator::sample_contexts`].
+const EXAMPLE_AUTHOR: &str = "example-author";
+
+/// Extensions of files loaded as templates.
+const TEMPLATE_EXTENSIONS: [&str; 2] = ["html", "hbs"];
+
 pub struct SiteGenerator {
     config: Config,
     handlebars: Handlebars<'static>,
     posts: Vec<Post>,
+    data: Value,
+    /// Strings of every site language, the default one first
+    translations: Vec<Translations>,
+    /// Language of the pages being generated
+    language: String,
 }
 
 impl SiteGenerator {
@@ -20,13 +57,24 @@ impl SiteGenerator {
         let mut handlebars = Handlebars::new();
 
         // Configure handlebars
-        handlebars.set_strict_mode(false);
+        handlebars.set_strict_mode(config.is_strict_templates());
         handlebars.register_escape_fn(handlebars::html_escape);
+        helpers::register(&mut handlebars, &config);
+        let translations = config
+            .get_languages()
+            .into_iter()
+            .map(|language| Translations::load(&config, language))
+            .collect::<Result<Vec<_>>>()?;
+        helpers::register_translations(&mut handlebars, translations.clone());
+        let language = config.get_language().to_string();
 
         Ok(SiteGenerator {
             config,
             handlebars,
             posts: Vec::new(),
+            data: json!({}),
+            translations,
+            language,
         })
     }
 
@@ -36,12 +84,339 @@ impl SiteGenerator {
         self.clean_output_dir()?;
         println!("✅ Output directory cleaned");
 
+        self.load_data()?;
+        println!("✅ Data files loaded");
+
+        // Шаблоны нужны до загрузки постов: из них рендерятся шорткоды
+        let templates = self.load_templates()?;
+        println!("📝 Loaded templates: {}", templates.join(", "));
+        println!("✅ Templates loaded");
+
         self.load_posts()?;
         println!("✅ Loaded {} posts", self.posts.len());
 
+        if self.config.is_image_processing_enabled() {
+            let (generated, cached) = self.process_images()?;
+            println!("✅ Images processed ({generated} generated, {cached} from cache)");
+        }
+
+        // Каждый язык получает свои страницы под `/<lang>/`, основной — в корне
+        let languages: Vec<String> = self
+            .config
+            .get_languages()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        for language in languages {
+            self.language = language;
+            if self.config.is_multilingual() {
+                println!("🌐 Generating pages for language '{}'", self.language);
+            }
+            self.generate_language_pages()?;
+        }
+        self.language = self.config.get_language().to_string();
+
+        self.generate_404_page()?;
+        println!("✅ 404 page generated");
+
+        self.copy_static_files()?;
+        println!("✅ Static files copied");
+
+        println!("🎉 Site generation completed successfully!");
+        Ok(())
+    }
+
+    /// Loads data files, templates and posts without writing anything, so
+    /// that page contexts can be inspected with [`Self::page_context`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if data files, templates or posts can't be loaded.
+    pub fn load(&mut self) -> Result<()> {
+        self.load_data()?;
         self.load_templates()?;
-        println!("✅ Templates loaded");
+        self.load_posts()
+    }
+
+    /// Switches the pages being built to another site language.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `language` is not one of the site languages.
+    pub fn set_language(&mut self, language: &str) -> Result<()> {
+        let languages = self.config.get_languages();
+        if !languages.contains(&language) {
+            return Err(ZahuyachError::InvalidInput(format!(
+                "Unknown language '{language}'. Site languages: {}",
+                languages.join(", ")
+            )));
+        }
+        self.language = language.to_string();
+        Ok(())
+    }
+
+    /// Returns the context the template of a page is rendered with,
+    /// including `data`. Pages of a post, tag, category, series or author
+    /// are picked by `name`: a slug or the name used in front matter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for unknown page types and for missing or unknown
+    /// names.
+    pub fn page_context(&self, page_type: &str, name: Option<&str>) -> Result<Value> {
+        let context = match page_type {
+            "post" => {
+                let slugs: Vec<String> = self.posts().map(|post| post.slug.clone()).collect();
+                let name = Self::required_name(page_type, name, &slugs)?;
+                let post = self
+                    .posts()
+                    .find(|post| post.slug == name)
+                    .ok_or_else(|| Self::unknown_name(page_type, name, &slugs))?;
+                self.create_post_data(post, &self.collect_series(), &self.collect_translations())
+            }
+            "index" => self.create_index_data(),
+            "archive" => self.create_archive_data(),
+            "about" => self.create_about_data(),
+            "tags" => self.create_tags_data(&self.collect_tags()),
+            "tag" => {
+                let (tag, posts) = self.find_named(page_type, name, self.collect_tags())?;
+                self.create_tag_data(&tag, posts)
+            }
+            "category" => {
+                let (category, posts) =
+                    self.find_named(page_type, name, self.collect_categories())?;
+                self.create_category_data(&category, posts)
+            }
+            "series_list" => self.create_series_list_data(&self.collect_series()),
+            "series" => {
+                let (series, posts) = self.find_named(page_type, name, self.collect_series())?;
+                self.create_series_data(&series, posts)
+            }
+            "authors" => self.create_authors_data(&self.collect_authors()),
+            "author" => {
+                let (id, posts) = self.find_named(page_type, name, self.collect_authors())?;
+                self.create_author_data(&id, posts)
+            }
+            "rss" => {
+                let posts: Vec<&Post> = self.posts().collect();
+                self.create_rss_data(&posts, &self.get_feed_context())
+            }
+            "404" => self.create_not_found_data(),
+            _ => {
+                return Err(ZahuyachError::InvalidInput(format!(
+                    "Unknown page type '{page_type}'. Available types: {}",
+                    PAGE_TYPES.join(", ")
+                )));
+            }
+        };
+
+        Ok(self.with_data(&context))
+    }
+
+    /// Returns example contexts of every page type, several per type where
+    /// the shape varies (e.g. a post with and without optional fields).
+    ///
+    /// The contexts don't depend on the site content: pages are built from
+    /// two example posts, one setting every optional front matter field and
+    /// one setting none, with the site's config and data files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the translations of the site can't be loaded.
+    pub fn sample_contexts(&self) -> Result<Vec<(&'static str, Vec<Value>)>> {
+        let mut config = self.config.clone();
+        config.authors.get_or_insert_with(HashMap::new).insert(
+            EXAMPLE_AUTHOR.to_string(),
+            AuthorConfig {
+                name: "Example Author".to_string(),
+                title: Some("Writer".to_string()),
+                bio: Some("Writes examples".to_string()),
+                avatar: Some("/static/avatar.png".to_string()),
+                email: Some("author@example.com".to_string()),
+                url: Some("https://example.com".to_string()),
+                social: Some(SocialConfig {
+                    github: Some("https://github.com/example".to_string()),
+                    twitter: None,
+                    linkedin: None,
+                    email: None,
+                    mastodon: None,
+                    youtube: None,
+                    instagram: None,
+                    facebook: None,
+                }),
+            },
+        );
+
+        let mut examples = Self::new(config)?;
+        examples.language.clone_from(&self.language);
+        examples.data = self.data.clone();
+        examples.posts = vec![examples.example_post(true), examples.example_post(false)];
+        Ok(examples.example_contexts())
+    }
+
+    fn example_contexts(&self) -> Vec<(&'static str, Vec<Value>)> {
+        let all_series = self.collect_series();
+        let all_translations = self.collect_translations();
+        let posts: Vec<&Post> = self.posts().collect();
+
+        let contexts = vec![
+            (
+                "post",
+                posts
+                    .iter()
+                    .map(|post| self.create_post_data(post, &all_series, &all_translations))
+                    .collect(),
+            ),
+            ("index", vec![self.create_index_data()]),
+            ("archive", vec![self.create_archive_data()]),
+            ("about", vec![self.create_about_data()]),
+            ("tags", vec![self.create_tags_data(&self.collect_tags())]),
+            (
+                "tag",
+                self.collect_tags()
+                    .into_iter()
+                    .map(|(tag, posts)| self.create_tag_data(&tag, posts))
+                    .collect(),
+            ),
+            (
+                "category",
+                self.collect_categories()
+                    .into_iter()
+                    .map(|(category, posts)| self.create_category_data(&category, posts))
+                    .collect(),
+            ),
+            (
+                "series_list",
+                vec![self.create_series_list_data(&all_series)],
+            ),
+            (
+                "series",
+                all_series
+                    .iter()
+                    .map(|(name, posts)| self.create_series_data(name, posts.clone()))
+                    .collect(),
+            ),
+            (
+                "authors",
+                vec![self.create_authors_data(&self.collect_authors())],
+            ),
+            // Зарегистрированные и незарегистрированные авторы отличаются полями
+            (
+                "author",
+                self.collect_authors()
+                    .into_iter()
+                    .map(|(id, posts)| self.create_author_data(&id, posts))
+                    .collect(),
+            ),
+            (
+                "rss",
+                vec![self.create_rss_data(&posts, &self.get_feed_context())],
+            ),
+            ("404", vec![self.create_not_found_data()]),
+        ];
+
+        contexts
+            .into_iter()
+            .map(|(page_type, variants)| {
+                let variants = variants.iter().map(|c| self.with_data(c)).collect();
+                (page_type, variants)
+            })
+            .collect()
+    }
+
+    /// Example post of the current language. A `full` one sets every
+    /// optional front matter field, the other one none of them.
+    fn example_post(&self, full: bool) -> Post {
+        let slug = if full { "example-post" } else { "minimal-post" };
+        let list = |items: &[&str]| full.then(|| items.iter().map(ToString::to_string).collect());
+        let text = |text: &str| full.then(|| text.to_string());
+
+        let toc = if full {
+            vec![TocEntry {
+                level: 2,
+                id: "intro".to_string(),
+                text: "Intro".to_string(),
+                children: vec![TocEntry {
+                    level: 3,
+                    id: "details".to_string(),
+                    text: "Details".to_string(),
+                    children: Vec::new(),
+                }],
+            }]
+        } else {
+            Vec::new()
+        };
+
+        Post {
+            front_matter: FrontMatter {
+                title: "Example post".to_string(),
+                date: if full { "2024-01-02" } else { "2024-01-01" }.to_string(),
+                author: None,
+                authors: list(&[EXAMPLE_AUTHOR, "Guest Author"]),
+                tags: list(&["example"]),
+                categories: list(&["Examples"]),
+                description: text("Example description"),
+                draft: full.then_some(false),
+                featured: full.then_some(true),
+                series: text("Example series"),
+                series_order: full.then_some(1),
+                template: None,
+                lang: Some(self.language.clone()),
+                translation_key: None,
+                image: text("/static/example.png"),
+                extra: BTreeMap::new(),
+            },
+            content: "Example text".to_string(),
+            html_content: "<p>Example text</p>".to_string(),
+            slug: slug.to_string(),
+            url: self.url(&format!("/posts/{slug}")),
+            file_path: Path::new(&self.config.build.content_dir).join(format!("{slug}.md")),
+            bundle_dir: None,
+            assets: Vec::new(),
+            toc,
+            excerpt: if full { "<p>Example text</p>" } else { "" }.to_string(),
+            summary: "Example text".to_string(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Finds the entry of `names` called `name` or with `name` as its slug.
+    fn find_named<'a>(
+        &self,
+        page_type: &str,
+        name: Option<&str>,
+        names: BTreeMap<String, Vec<&'a Post>>,
+    ) -> Result<(String, Vec<&'a Post>)> {
+        let available: Vec<String> = names.keys().cloned().collect();
+        let name = Self::required_name(page_type, name, &available)?;
+        names
+            .into_iter()
+            .find(|(entry, _)| entry == name || self.slugify(entry) == name)
+            .ok_or_else(|| Self::unknown_name(page_type, name, &available))
+    }
+
+    fn required_name<'a>(
+        page_type: &str,
+        name: Option<&'a str>,
+        available: &[String],
+    ) -> Result<&'a str> {
+        name.ok_or_else(|| {
+            ZahuyachError::InvalidInput(format!(
+                "Page type '{page_type}' needs a name. Available: {}",
+                available.join(", ")
+            ))
+        })
+    }
 
+    fn unknown_name(page_type: &str, name: &str, available: &[String]) -> ZahuyachError {
+        ZahuyachError::InvalidInput(format!(
+            "No {page_type} page named '{name}'. Available: {}",
+            available.join(", ")
+        ))
+    }
+
+    /// Generates posts and listing pages of the current language.
+    fn generate_language_pages(&self) -> Result<()> {
         self.generate_posts()?;
         println!("✅ Individual posts generated");
 
@@ -64,18 +439,19 @@ impl SiteGenerator {
             println!("✅ Categories pages generated");
         }
 
+        if self.config.is_series_enabled() {
+            self.generate_series_pages()?;
+            println!("✅ Series pages generated");
+        }
+
+        self.generate_authors_pages()?;
+        println!("✅ Authors pages generated");
+
         if self.config.is_rss_enabled() {
             self.generate_rss_feed()?;
             println!("✅ RSS feed generated");
         }
 
-        self.generate_404_page()?;
-        println!("✅ 404 page generated");
-
-        self.copy_static_files()?;
-        println!("✅ Static files copied");
-
-        println!("🎉 Site generation completed successfully!");
         Ok(())
     }
 
@@ -99,6 +475,7 @@ impl SiteGenerator {
 
     fn load_posts(&mut self) -> Result<()> {
         let content_dir = Path::new(&self.config.build.content_dir);
+        let options = RenderOptions::from_config(&self.config);
 
         if !content_dir.exists() {
             return Err(ZahuyachError::InvalidInput(format!(
@@ -107,15 +484,27 @@ impl SiteGenerator {
             )));
         }
 
-        for entry in WalkDir::new(content_dir) {
+        let mut entries = WalkDir::new(content_dir).into_iter();
+
+        while let Some(entry) = entries.next() {
             let entry = entry.map_err(|e| ZahuyachError::Io(e.into()))?;
             let path = entry.path();
 
-            if path.extension().map_or(false, |ext| ext == "md") {
-                let post = Post::from_file(path)?;
-                if self.config.include_drafts() || !post.is_draft() {
-                    self.posts.push(post);
+            // Директория с index.md - это page bundle: остальные файлы в ней
+            // являются ресурсами поста, а не отдельными постами
+            if entry.depth() > 0 && entry.file_type().is_dir() {
+                let index_path = path.join(BUNDLE_INDEX);
+                if index_path.is_file() {
+                    let post = self.load_post(&index_path, &options)?;
+                    self.add_post(post);
+                    entries.skip_current_dir();
                 }
+                continue;
+            }
+
+            if path.extension().map_or(false, |ext| ext == "md") {
+                let post = self.load_post(path, &options)?;
+                self.add_post(post);
             }
         }
 
@@ -126,76 +515,368 @@ impl SiteGenerator {
         Ok(())
     }
 
-    fn load_templates(&mut self) -> Result<()> {
-        let templates_dir = Path::new(&self.config.build.templates_dir);
+    /// Loads a post and publishes it under the prefix of its language.
+    /// Posts in a language tree (`content/<lang>/`) default to its language.
+    fn load_post(&self, path: &Path, options: &RenderOptions) -> Result<Post> {
+        let tree_language = self.tree_language(path);
+        let mut options = tree_language
+            .as_deref()
+            .map_or_else(|| options.clone(), |lang| options.with_language(lang));
+        if let Some(translations) = self
+            .translations
+            .iter()
+            .find(|translations| translations.language() == options.language)
+        {
+            options.anchor_label = translations.translate("post.anchor_link", None, &[]);
+        }
+
+        let mut post = Post::from_file_with_shortcodes(path, &options, self)?;
+        if post.front_matter.lang.is_none() {
+            post.front_matter.lang = tree_language;
+        }
+
+        let prefix = self.language_prefix(self.post_language(&post));
+        if !prefix.is_empty() {
+            post.relocate(&prefix, &helpers::base_path(&self.config.site.base_url));
+        }
+        Ok(post)
+    }
+
+    /// Returns the site language whose content tree `path` is in.
+    fn tree_language(&self, path: &Path) -> Option<String> {
+        if !self.config.is_multilingual() {
+            return None;
+        }
+        let relative = path.strip_prefix(&self.config.build.content_dir).ok()?;
+        let first = relative.components().next()?.as_os_str().to_str()?;
+        self.config
+            .get_languages()
+            .contains(&first)
+            .then(|| first.to_string())
+    }
+
+    /// Resizes images used by posts and rewrites their `<img>` tags into
+    /// responsive markup. Handles page bundle files and `/static/` images.
+    fn process_images(&mut self) -> Result<(usize, usize)> {
+        let mut processor = ImageProcessor::new(ImageSettings::from_config(&self.config)?);
+        let output_dir = Path::new(&self.config.build.output_dir);
+        let static_dir = Path::new(&self.config.build.static_dir);
+        let base_path = helpers::base_path(&self.config.site.base_url);
+
+        for post in &mut self.posts {
+            let bundle_prefix = format!("{}/", post.url.trim_start_matches('/'));
+            let bundle_dir = post.bundle_dir.as_deref();
+
+            let resolve = |src: &str| {
+                // Ссылки на бандл начинаются с пути из base_url, `/static/` - нет
+                let src = src
+                    .strip_prefix(base_path.as_str())
+                    .filter(|path| path.starts_with('/'))
+                    .unwrap_or(src);
+                let path = src.strip_prefix('/')?;
+                if path.contains(['?', '#']) || path.contains("..") {
+                    return None;
+                }
+                let source = match path.strip_prefix("static/") {
+                    Some(file) => static_dir.join(file),
+                    None => bundle_dir?.join(path.strip_prefix(&bundle_prefix)?),
+                };
+                let variants_dir = output_dir.join(path).parent()?.to_path_buf();
+                Some((source, variants_dir))
+            };
+            post.html_content = processor.rewrite_html(&post.html_content, resolve)?;
+            post.excerpt = processor.rewrite_html(&post.excerpt, resolve)?;
+        }
+
+        Ok((processor.generated, processor.cached))
+    }
+
+    /// Reports rendering warnings and keeps the post unless it is a hidden draft.
+    fn add_post(&mut self, post: Post) {
+        // Предупреждения идут в stderr, чтобы не портить вывод `zahuyach context`
+        for warning in &post.warnings {
+            eprintln!("⚠️  {warning}");
+        }
+        if self.config.include_drafts() || !post.is_draft() {
+            self.posts.push(post);
+        }
+    }
+
+    fn load_data(&mut self) -> Result<()> {
+        self.data = data::load_data_dir(Path::new(self.config.get_data_dir()))?;
+        Ok(())
+    }
+
+    /// Loads templates in layers: the embedded basic theme, then the
+    /// selected theme, then the project's `templates_dir`. Each layer
+    /// overrides templates of the same name from the previous ones.
+    ///
+    /// Returns the loaded template names with the source of overridden ones.
+    fn load_templates(&mut self) -> Result<Vec<String>> {
+        // Имя шаблона -> откуда он загружен
+        let mut loaded_templates: BTreeMap<String, String> = BTreeMap::new();
+
+        for (relative, data) in templates::embedded_files(templates::TEMPLATES_DIR) {
+            if let Some(name) = Self::template_name(Path::new(&relative)) {
+                self.register_template(&name, &String::from_utf8_lossy(&data))?;
+                loaded_templates.insert(name, "basic".to_string());
+            }
+        }
+
+        if let Some(theme_dir) = self.theme_dir()? {
+            let theme = self.config.build.theme.clone().unwrap_or_default();
+            self.load_template_dir(
+                &theme_dir.join(templates::TEMPLATES_DIR),
+                &format!("theme '{theme}'"),
+                &mut loaded_templates,
+            )?;
+        }
+
+        let templates_dir = PathBuf::from(&self.config.build.templates_dir);
+        self.load_template_dir(&templates_dir, "project", &mut loaded_templates)?;
+
+        let names: Vec<String> = loaded_templates
+            .iter()
+            .map(|(name, source)| {
+                if source == "basic" {
+                    name.clone()
+                } else {
+                    format!("{name} ({source})")
+                }
+            })
+            .collect();
+        Ok(names)
+    }
 
-        if !templates_dir.exists() {
+    /// Returns the selected theme's directory.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a theme is configured but its directory doesn't exist.
+    fn theme_dir(&self) -> Result<Option<PathBuf>> {
+        let Some(theme_dir) = self.config.get_theme_dir() else {
+            return Ok(None);
+        };
+        if !theme_dir.is_dir() {
             return Err(ZahuyachError::InvalidInput(format!(
-                "Templates directory '{}' does not exist",
-                templates_dir.display()
+                "Theme '{}' not found: directory '{}' does not exist",
+                self.config.build.theme.as_deref().unwrap_or_default(),
+                theme_dir.display()
             )));
         }
+        Ok(Some(theme_dir))
+    }
+
+    /// Registers every template in `dir`, overriding earlier layers.
+    /// A missing directory is skipped.
+    fn load_template_dir(
+        &mut self,
+        dir: &Path,
+        source: &str,
+        loaded_templates: &mut BTreeMap<String, String>,
+    ) -> Result<()> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
 
-        let mut loaded_templates = Vec::new();
+        // Имя шаблона -> файл в этой директории
+        let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
 
-        // Load all .html files as templates
-        for entry in WalkDir::new(templates_dir) {
+        for entry in WalkDir::new(dir).sort_by_file_name() {
             let entry = entry.map_err(|e| ZahuyachError::Io(e.into()))?;
             let path = entry.path();
 
-            if path.extension().map_or(false, |ext| ext == "html") {
-                let template_name = path.file_stem().and_then(|s| s.to_str()).ok_or_else(|| {
-                    ZahuyachError::InvalidInput("Invalid template name".to_string())
-                })?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Some(template_name) = path.strip_prefix(dir).ok().and_then(Self::template_name)
+            else {
+                continue;
+            };
+
+            if let Some(existing) = files.get(&template_name) {
+                return Err(ZahuyachError::InvalidInput(format!(
+                    "Template '{template_name}' is defined twice: '{}' and '{}'",
+                    existing.display(),
+                    path.display()
+                )));
+            }
+
+            self.register_template(&template_name, &fs::read_to_string(path)?)?;
+            loaded_templates.insert(template_name.clone(), source.to_string());
+            files.insert(template_name, path.to_path_buf());
+        }
 
-                let template_content = fs::read_to_string(path)?;
+        Ok(())
+    }
 
-                self.handlebars
-                    .register_template_string(template_name, template_content)
-                    .map_err(|e| ZahuyachError::InvalidInput(format!("Template error: {}", e)))?;
+    fn register_template(&mut self, name: &str, content: &str) -> Result<()> {
+        self.handlebars
+            .register_template_string(name, content)
+            .map_err(|e| ZahuyachError::InvalidInput(format!("Template error: {e}")))
+    }
 
-                loaded_templates.push(template_name.to_string());
-            }
+    /// Returns the name a template file is registered under: its path
+    /// relative to the templates directory without the extension, e.g.
+    /// `partials/header`. `None` for files that aren't templates.
+    fn template_name(relative: &Path) -> Option<String> {
+        let extension = relative.extension()?.to_str()?;
+        if !TEMPLATE_EXTENSIONS.contains(&extension) {
+            return None;
         }
 
-        println!("📝 Loaded templates: {}", loaded_templates.join(", "));
-        Ok(())
+        let relative = relative.with_extension("");
+        let parts: Option<Vec<&str>> = relative
+            .components()
+            .map(|c| c.as_os_str().to_str())
+            .collect();
+        parts.map(|parts| parts.join("/"))
     }
 
     fn has_template(&self, name: &str) -> bool {
         self.handlebars.get_template(name).is_some()
     }
 
+    /// Renders a template, exposing the loaded data files as `data`.
+    fn render(&self, name: &str, context: &Value) -> std::result::Result<String, RenderError> {
+        self.handlebars.render(name, &self.with_data(context))
+    }
+
+    /// Renders a template into the output file `page`. Errors name the
+    /// template, line and page.
+    fn render_page(&self, name: &str, page: &Path, context: &Value) -> Result<String> {
+        let context = self.with_data(context);
+        self.handlebars.render(name, &context).map_err(|e| {
+            let page = page
+                .strip_prefix(&self.config.build.output_dir)
+                .unwrap_or(page);
+            ZahuyachError::InvalidInput(describe_render_error(&e, name, Some(page), &context))
+        })
+    }
+
+    /// Adds the loaded data files as `data` and, with SEO enabled, the
+    /// `seo` metadata of pages.
+    fn with_data(&self, context: &Value) -> Value {
+        // Страницы записей приходят уже со своим `seo`
+        let seo = context
+            .get("page")
+            .filter(|_| self.config.is_seo_enabled() && context.get("seo").is_none())
+            .map(|page| self.get_seo_context(page, None));
+        let mut context = context.clone();
+        if let Value::Object(map) = &mut context {
+            map.insert("data".to_string(), self.data.clone());
+            if let Some(seo) = seo {
+                map.insert("seo".to_string(), seo);
+            }
+        }
+        context
+    }
+
     fn generate_posts(&self) -> Result<()> {
         let output_dir = Path::new(&self.config.build.output_dir);
 
-        if !self.has_template("post") {
-            println!("⚠️  Template 'post' not found, skipping individual post generation");
-            return Ok(());
+        let mut skipped = 0;
+        let all_series = self.collect_series();
+        let all_translations = self.collect_translations();
+
+        for post in self.posts() {
+            let Some(template) = self.resolve_post_template(post) else {
+                skipped += 1;
+                continue;
+            };
+
+            let post_data = self.create_post_data(post, &all_series, &all_translations);
+            let post_dir = output_dir.join(post.url.trim_start_matches('/'));
+            // Бандл становится директорией `posts/<slug>/` со своими файлами,
+            // иначе `/posts/<slug>` отдавался бы хостингом как директория ресурсов
+            let post_path = if post.is_bundle() {
+                post_dir.join("index.html")
+            } else {
+                post_dir.with_file_name(format!("{}.html", post.slug))
+            };
+            let html = self.render_page(&template, &post_path, &post_data)?;
+
+            if let Some(parent) = post_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(post_path, html)?;
+
+            Self::copy_post_assets(post, &post_dir)?;
         }
 
-        let posts_dir = output_dir.join("posts");
-        fs::create_dir_all(&posts_dir)?;
+        if skipped > 0 {
+            println!("⚠️  Template 'post' not found, skipped {skipped} post(s)");
+        }
 
-        for post in &self.posts {
-            let post_data = self.create_post_data(post);
-            let html = self.handlebars.render("post", &post_data).map_err(|e| {
-                ZahuyachError::InvalidInput(format!("Template render error: {}", e))
-            })?;
+        Ok(())
+    }
 
-            let post_path = posts_dir.join(format!("{}.html", post.slug));
-            fs::write(post_path, html)?;
+    /// Picks the template for a post: front matter `template`/`layout` first,
+    /// then the `[layouts]` default for the post's directory, then `post`.
+    ///
+    /// Unknown templates are reported and replaced with `post`. Returns `None`
+    /// when even `post` is missing.
+    fn resolve_post_template(&self, post: &Post) -> Option<String> {
+        let content_dir = Path::new(&self.config.build.content_dir);
+        let source = post.bundle_dir.as_deref().unwrap_or(&post.file_path);
+        let relative = source.strip_prefix(content_dir).unwrap_or(source);
+
+        let requested = post
+            .front_matter
+            .template
+            .as_deref()
+            .or_else(|| self.config.get_directory_layout(relative))
+            .map(|name| {
+                TEMPLATE_EXTENSIONS
+                    .iter()
+                    .find_map(|ext| name.strip_suffix(&format!(".{ext}")))
+                    .unwrap_or(name)
+            });
+
+        if let Some(name) = requested {
+            if self.has_template(name) {
+                return Some(name.to_string());
+            }
+            println!(
+                "⚠️  Template '{}' referenced by '{}' not found, falling back to 'post'",
+                name,
+                post.file_path.display()
+            );
+        }
+
+        self.has_template("post").then(|| "post".to_string())
+    }
+
+    /// Copies page bundle assets to `assets_dir` (`posts/<slug>/`), the
+    /// directory holding the rendered post's `index.html`.
+    fn copy_post_assets(post: &Post, assets_dir: &Path) -> Result<()> {
+        let Some(bundle_dir) = &post.bundle_dir else {
+            return Ok(());
+        };
+
+        for asset in &post.assets {
+            let target = assets_dir.join(asset);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(bundle_dir.join(asset), target)?;
         }
 
         Ok(())
     }
 
     fn generate_index(&self) -> Result<()> {
-        let output_dir = Path::new(&self.config.build.output_dir);
+        let index_path = self.output_dir().join("index.html");
+        let html = self.render_page("index", &index_path, &self.create_index_data())?;
+        fs::write(index_path, html)?;
+
+        Ok(())
+    }
 
-        let posts_refs: Vec<&Post> = self.posts.iter().collect();
+    fn create_index_data(&self) -> Value {
+        let posts_refs: Vec<&Post> = self.posts().collect();
 
-        let context = json!({
+        json!({
             "site": self.get_site_context(),
             "posts": self.get_posts_list_context(posts_refs, self.config.get_posts_per_page()),
             "popular_tags": self.get_popular_tags(),
@@ -203,36 +884,32 @@ impl SiteGenerator {
             "recent_posts": self.get_recent_posts(self.config.get_recent_posts_limit()),
             "stats": self.get_site_stats(),
             "page": {
-                "title": "Home",
-                "description": self.config.site.description,
-                "url": "/"
+                "title": self.t("index.title", &[]),
+                "description": self.config.site_for_language(&self.language).description,
+                "url": self.url("/")
             }
-        });
-
-        let html = self
-            .handlebars
-            .render("index", &context)
-            .map_err(|e| ZahuyachError::InvalidInput(format!("Template render error: {}", e)))?;
-
-        let index_path = output_dir.join("index.html");
-        fs::write(index_path, html)?;
-
-        Ok(())
+        })
     }
 
     fn generate_archive(&self) -> Result<()> {
-        let output_dir = Path::new(&self.config.build.output_dir);
-
         // Проверяем наличие шаблона archive
         if !self.has_template("archive") {
             println!("⚠️  Template 'archive' not found, skipping archive generation");
             return Ok(());
         }
 
+        let archive_path = self.output_dir().join("archive.html");
+        let html = self.render_page("archive", &archive_path, &self.create_archive_data())?;
+        fs::write(archive_path, html)?;
+
+        Ok(())
+    }
+
+    fn create_archive_data(&self) -> Value {
         // Group posts by year and month
         let mut posts_by_date: HashMap<String, HashMap<String, Vec<&Post>>> = HashMap::new();
 
-        for post in &self.posts {
+        for post in self.posts() {
             let date_parts: Vec<&str> = post.front_matter.date.split('-').collect();
             if date_parts.len() >= 2 {
                 let year = date_parts[0];
@@ -265,7 +942,7 @@ impl SiteGenerator {
                 for post in month_posts {
                     year_posts.push(json!({
                         "title": post.front_matter.title,
-                        "url": format!("/posts/{}", post.slug),
+                        "url": post.url,
                         "date": self.format_date_short(&post.front_matter.date),
                         "date_iso": post.front_matter.date,
                         "tags": post.front_matter.tags.as_ref().unwrap_or(&vec![])
@@ -279,34 +956,24 @@ impl SiteGenerator {
             }));
         }
 
-        let context = json!({
+        json!({
             "site": self.get_site_context(),
             "posts_by_year": posts_by_year,
             "posts_by_date": posts_by_date, // Keep for backward compatibility
-            "total_posts": self.posts.len(),
+            "total_posts": self.posts().count(),
             "categories": self.get_categories_tree(),
             "popular_tags": self.get_popular_tags(),
             "recent_posts": self.get_recent_posts(self.config.get_recent_posts_limit()),
             "page": {
-                "title": "Archive",
-                "description": "Archive of all blog posts",
-                "url": "/archive"
+                "title": self.t("archive.title", &[]),
+                "description": self.t("archive.description", &[]),
+                "url": self.url("/archive")
             }
-        });
-
-        let html = self
-            .handlebars
-            .render("archive", &context)
-            .map_err(|e| ZahuyachError::InvalidInput(format!("Template render error: {}", e)))?;
-
-        let archive_path = output_dir.join("archive.html");
-        fs::write(archive_path, html)?;
-
-        Ok(())
+        })
     }
 
     fn generate_tags_pages(&self) -> Result<()> {
-        let output_dir = Path::new(&self.config.build.output_dir);
+        let output_dir = self.output_dir();
         let tags_dir = output_dir.join("tags");
 
         // Проверяем наличие шаблонов tags и tag
@@ -314,80 +981,84 @@ impl SiteGenerator {
             println!("⚠️  Templates 'tags' and 'tag' not found, skipping tags generation");
             return Ok(());
         }
+        fs::create_dir_all(&tags_dir)?;
 
-        // Collect all tags
-        let mut all_tags: HashMap<String, Vec<&Post>> = HashMap::new();
-
-        for post in &self.posts {
-            if let Some(tags) = &post.front_matter.tags {
-                for tag in tags {
-                    all_tags
-                        .entry(tag.clone())
-                        .or_insert_with(Vec::new)
-                        .push(post);
-                }
-            }
-        }
+        let all_tags = self.collect_tags();
 
         // Generate tags index page only if template exists
         if self.has_template("tags") {
-            let tags_context = json!({
-                "site": self.get_site_context(),
-                "all_tags": all_tags.iter().map(|(tag, posts)| {
-                    json!({
-                        "name": tag,
-                        "count": posts.len(),
-                        "slug": self.slugify(tag)
-                    })
-                }).collect::<Vec<_>>(),
-                "categories": self.get_categories_tree(),
-                "page": {
-                    "title": "Tags",
-                    "description": "Archive of all blog posts",
-                    "url": "/tags"
-                }
-            });
-
-            let tags_html = self.handlebars.render("tags", &tags_context).map_err(|e| {
-                ZahuyachError::InvalidInput(format!("Template render error: {}", e))
-            })?;
-
-            fs::write(tags_dir.join("index.html"), tags_html)?;
+            let tags_path = tags_dir.join("index.html");
+            let tags_context = self.create_tags_data(&all_tags);
+            let tags_html = self.render_page("tags", &tags_path, &tags_context)?;
+            fs::write(tags_path, tags_html)?;
         }
 
         // Generate individual tag pages only if template exists
         if self.has_template("tag") {
-            for (tag, posts) in all_tags {
-                let tag_slug = self.slugify(&tag);
-                let tag_context = json!({
-                    "site": self.get_site_context(),
-                    "tag": {
-                        "name": tag,
-                        "slug": tag_slug
-                    },
-                    "posts": self.get_posts_list_context(posts, 0),
-                    "categories": self.get_categories_tree(),
-                    "popular_tags": self.get_popular_tags(),
-                    "page": {
-                        "title": format!("Тег: {}", tag),
-                        "description": format!("Все статьи с тегом {}", tag),
-                        "url": format!("/tags/{}", tag_slug)
-                    }
-                });
+            for (tag, posts) in &all_tags {
+                let tag_path = tags_dir.join(format!("{}.html", self.slugify(tag)));
+                let tag_context = self.create_tag_data(tag, posts.clone());
+                let tag_html = self.render_page("tag", &tag_path, &tag_context)?;
+                fs::write(tag_path, tag_html)?;
+            }
+        }
+
+        Ok(())
+    }
 
-                let tag_html = self.handlebars.render("tag", &tag_context).map_err(|e| {
-                    ZahuyachError::InvalidInput(format!("Template render error: {}", e))
-                })?;
+    /// Groups posts by tag.
+    fn collect_tags(&self) -> BTreeMap<String, Vec<&Post>> {
+        let mut all_tags: BTreeMap<String, Vec<&Post>> = BTreeMap::new();
 
-                fs::write(tags_dir.join(format!("{}.html", tag_slug)), tag_html)?;
+        for post in self.posts() {
+            for tag in post.front_matter.tags.iter().flatten() {
+                all_tags.entry(tag.clone()).or_default().push(post);
             }
         }
 
-        Ok(())
+        all_tags
+    }
+
+    fn create_tags_data(&self, all_tags: &BTreeMap<String, Vec<&Post>>) -> Value {
+        json!({
+            "site": self.get_site_context(),
+            "all_tags": all_tags.iter().map(|(tag, posts)| {
+                json!({
+                    "name": tag,
+                    "count": posts.len(),
+                    "slug": self.slugify(tag)
+                })
+            }).collect::<Vec<_>>(),
+            "categories": self.get_categories_tree(),
+            "page": {
+                "title": self.t("tags.title", &[]),
+                "description": self.t("tags.description", &[]),
+                "url": self.url("/tags")
+            }
+        })
+    }
+
+    fn create_tag_data(&self, tag: &str, posts: Vec<&Post>) -> Value {
+        let tag_slug = self.slugify(tag);
+        json!({
+            "site": self.get_site_context(),
+            "tag": {
+                "name": tag,
+                "slug": tag_slug
+            },
+            "posts": self.get_posts_list_context(posts, 0),
+            "categories": self.get_categories_tree(),
+            "popular_tags": self.get_popular_tags(),
+            "page": {
+                "title": self.t("tag.title", &[("name", tag)]),
+                "description": self.t("tag.description", &[("name", tag)]),
+                "url": self.url(&format!("/tags/{tag_slug}"))
+            }
+        })
     }
 
     fn generate_categories_pages(&self) -> Result<()> {
-        let output_dir = Path::new(&self.config.build.output_dir);
+        let output_dir = self.output_dir();
         let categories_dir = output_dir.join("categories");
 
         // Проверяем наличие шаблона category
@@ -396,128 +1067,315 @@ impl SiteGenerator {
             return Ok(());
         }
 
-        // Collect all categories
-        let mut all_categories: HashMap<String, Vec<&Post>> = HashMap::new();
-
-        for post in &self.posts {
-            if let Some(categories) = &post.front_matter.categories {
-                for category in categories {
-                    all_categories
-                        .entry(category.clone())
-                        .or_insert_with(Vec::new)
-                        .push(post);
-                }
-            }
-        }
-
         // Generate individual category pages
-        for (category, posts) in all_categories {
+        for (category, posts) in self.collect_categories() {
             let category_slug = self.slugify(&category);
-            let category_context = json!({
-                "site": self.get_site_context(),
-                "category": {
-                    "name": category,
-                    "slug": category_slug
-                },
-                "posts": self.get_posts_list_context(posts, 0),
-                "categories": self.get_categories_tree(),
-                "popular_tags": self.get_popular_tags(),
-                "page": {
-                    "title": format!("Category: {}", category),
-                    "description": format!("All articles in category {}", category),
-                    "url": format!("/categories/{}", category_slug)
-                }
-            });
+            let category_context = self.create_category_data(&category, posts);
 
-            let category_html = self
-                .handlebars
-                .render("category", &category_context)
-                .map_err(|e| {
-                    ZahuyachError::InvalidInput(format!("Template render error: {}", e))
-                })?;
+            let category_path = categories_dir.join(&category_slug).join("index.html");
+            let category_html = self.render_page("category", &category_path, &category_context)?;
 
             fs::create_dir_all(categories_dir.join(&category_slug))?;
-            fs::write(
-                categories_dir.join(&category_slug).join("index.html"),
-                category_html,
-            )?;
+            fs::write(category_path, category_html)?;
         }
 
         Ok(())
     }
 
-    fn generate_rss_feed(&self) -> Result<()> {
-        let output_dir = Path::new(&self.config.build.output_dir);
+    /// Groups posts by category.
+    fn collect_categories(&self) -> BTreeMap<String, Vec<&Post>> {
+        let mut all_categories: BTreeMap<String, Vec<&Post>> = BTreeMap::new();
 
-        // Check rss template
-        if !self.has_template("rss") {
-            println!("⚠️  Template 'rss' not found, generating simple RSS feed");
+        for post in self.posts() {
+            for category in post.front_matter.categories.iter().flatten() {
+                all_categories
+                    .entry(category.clone())
+                    .or_default()
+                    .push(post);
+            }
+        }
 
-            // Создаем простой RSS без шаблона
-            let rss_content = self.generate_simple_rss()?;
-            fs::write(output_dir.join(self.config.get_rss_filename()), rss_content)?;
+        all_categories
+    }
+
+    fn create_category_data(&self, category: &str, posts: Vec<&Post>) -> Value {
+        let category_slug = self.slugify(category);
+        json!({
+            "site": self.get_site_context(),
+            "category": {
+                "name": category,
+                "slug": category_slug
+            },
+            "posts": self.get_posts_list_context(posts, 0),
+            "categories": self.get_categories_tree(),
+            "popular_tags": self.get_popular_tags(),
+            "page": {
+                "title": self.t("category.title", &[("name", category)]),
+                "description": self.t("category.description", &[("name", category)]),
+                "url": self.url(&format!("/categories/{category_slug}"))
+            }
+        })
+    }
+
+    fn generate_series_pages(&self) -> Result<()> {
+        let series_dir = self.output_dir().join("series");
+
+        if !self.has_template("series") && !self.has_template("series_list") {
+            println!(
+                "⚠️  Templates 'series' and 'series_list' not found, skipping series generation"
+            );
             return Ok(());
         }
 
-        let rss_posts: Vec<Value> = self.posts.iter()
-                .take(self.config.get_rss_limit())
-                .map(|post| {
-                    json!({
-                        "title": post.front_matter.title,
-                        "link": format!("{}/posts/{}", self.config.site.base_url, post.slug),
-                        "description": post.front_matter.description.as_ref().unwrap_or(&post.front_matter.title),
-                        "content": post.html_content,
-                        "pub_date": post.front_matter.date,
-                        "author": post.front_matter.author.as_ref().unwrap_or(&self.config.site.author)
-                    })
-                })
-                .collect();
+        let all_series = self.collect_series();
+        fs::create_dir_all(&series_dir)?;
+
+        // Generate series listing page only if template exists
+        if self.has_template("series_list") {
+            let path = series_dir.join("index.html");
+            let context = self.create_series_list_data(&all_series);
+            let html = self.render_page("series_list", &path, &context)?;
+            fs::write(path, html)?;
+        }
+
+        // Generate individual series pages only if template exists
+        if self.has_template("series") {
+            for (name, posts) in &all_series {
+                let slug = self.slugify(name);
+                let context = self.create_series_data(name, posts.clone());
+
+                let path = series_dir.join(&slug).join("index.html");
+                let html = self.render_page("series", &path, &context)?;
+
+                fs::create_dir_all(series_dir.join(&slug))?;
+                fs::write(path, html)?;
+            }
+        }
 
-        let rss_context = json!({
+        Ok(())
+    }
+
+    fn create_series_list_data(&self, all_series: &BTreeMap<String, Vec<&Post>>) -> Value {
+        json!({
             "site": self.get_site_context(),
-            "posts": rss_posts,
-            "build_date": chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S %z").to_string()
+            "all_series": all_series.iter().map(|(name, parts)| {
+                self.get_series_context(name, parts, None)
+            }).collect::<Vec<_>>(),
+            "categories": self.get_categories_tree(),
+            "popular_tags": self.get_popular_tags(),
+            "page": {
+                "title": self.t("series_list.title", &[]),
+                "description": self.t("series_list.description", &[]),
+                "url": self.url("/series/")
+            }
+        })
+    }
+
+    fn create_series_data(&self, name: &str, posts: Vec<&Post>) -> Value {
+        let slug = self.slugify(name);
+        json!({
+            "site": self.get_site_context(),
+            "series": self.get_series_context(name, &posts, None),
+            "posts": self.get_posts_list_context(posts, 0),
+            "categories": self.get_categories_tree(),
+            "popular_tags": self.get_popular_tags(),
+            "page": {
+                "title": self.t("series.title", &[("name", name)]),
+                "description": self.t("series.description", &[("name", name)]),
+                "url": self.url(&format!("/series/{slug}/"))
+            }
+        })
+    }
+
+    fn generate_authors_pages(&self) -> Result<()> {
+        let authors_dir = self.output_dir().join("authors");
+        let site = self.config.site_for_language(&self.language);
+
+        if !self.has_template("authors") && !self.has_template("author") {
+            println!("⚠️  Templates 'authors' and 'author' not found, skipping authors generation");
+            return Ok(());
+        }
+
+        let all_authors = self.collect_authors();
+        fs::create_dir_all(&authors_dir)?;
+
+        // Generate authors index page only if template exists
+        if self.has_template("authors") {
+            let context = self.create_authors_data(&all_authors);
+            let path = authors_dir.join("index.html");
+            let html = self.render_page("authors", &path, &context)?;
+            fs::write(path, html)?;
+        }
+
+        // Generate individual author pages and feeds
+        for (id, posts) in &all_authors {
+            let author = self.get_author_context(id);
+            let slug = self.slugify(id);
+            let author_output = authors_dir.join(&slug);
+            fs::create_dir_all(&author_output)?;
+
+            if self.has_template("author") {
+                let context = self.create_author_data(id, posts.clone());
+                let path = author_output.join("index.html");
+                let html = self.render_page("author", &path, &context)?;
+                fs::write(path, html)?;
+            }
+
+            if self.config.is_rss_enabled() {
+                let feed = json!({
+                    "title": format!("{} - {}", site.title, author["name"].as_str().unwrap_or(id)),
+                    "description": author["bio"].as_str().unwrap_or(&site.description),
+                    "link": format!("{}{}", site.base_url, self.url(&format!("/authors/{slug}/")))
+                });
+                self.write_feed(
+                    posts,
+                    &feed,
+                    &author_output.join(self.config.get_rss_filename()),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_authors_data(&self, all_authors: &BTreeMap<String, Vec<&Post>>) -> Value {
+        json!({
+            "site": self.get_site_context(),
+            "all_authors": all_authors.iter().map(|(id, posts)| {
+                let mut author = self.get_author_context(id);
+                author["count"] = json!(posts.len());
+                author
+            }).collect::<Vec<_>>(),
+            "categories": self.get_categories_tree(),
+            "popular_tags": self.get_popular_tags(),
+            "page": {
+                "title": self.t("authors.title", &[]),
+                "description": self.t("authors.description", &[]),
+                "url": self.url("/authors/")
+            }
+        })
+    }
+
+    fn create_author_data(&self, id: &str, posts: Vec<&Post>) -> Value {
+        let author = self.get_author_context(id);
+        let name = author["name"].as_str().unwrap_or(id);
+        let page = json!({
+            "title": self.t("author.title", &[("name", name)]),
+            "description": self.t("author.description", &[("name", name)]),
+            "url": self.url(&format!("/authors/{}/", self.slugify(id)))
         });
+        json!({
+            "site": self.get_site_context(),
+            "author": author,
+            "posts": self.get_posts_list_context(posts, 0),
+            "categories": self.get_categories_tree(),
+            "popular_tags": self.get_popular_tags(),
+            "page": page
+        })
+    }
+
+    fn generate_rss_feed(&self) -> Result<()> {
+        let output_dir = self.output_dir();
+
+        // Check rss template
+        if !self.has_template("rss") {
+            println!("⚠️  Template 'rss' not found, generating simple RSS feed");
+        }
+
+        let posts: Vec<&Post> = self.posts().collect();
+        let feed = self.get_feed_context();
+
+        self.write_feed(
+            &posts,
+            &feed,
+            &output_dir.join(self.config.get_rss_filename()),
+        )
+    }
+
+    /// Builds the `feed` context of the site feed in the current language.
+    fn get_feed_context(&self) -> Value {
+        let site = self.config.site_for_language(&self.language);
+        json!({
+            "title": site.title,
+            "description": site.description,
+            "link": format!("{}{}", site.base_url, self.language_prefix(&self.language))
+        })
+    }
 
-        let rss_xml = self.handlebars.render("rss", &rss_context).map_err(|e| {
-            ZahuyachError::InvalidInput(format!("RSS template render error: {}", e))
-        })?;
+    /// Renders an RSS feed for `posts` with the `rss` template, or as a
+    /// simple built-in feed when the template is missing.
+    fn write_feed(&self, posts: &[&Post], feed: &Value, path: &Path) -> Result<()> {
+        if !self.has_template("rss") {
+            // Создаем простой RSS без шаблона
+            let rss_content = self.generate_simple_rss(posts, feed);
+            fs::write(path, rss_content)?;
+            return Ok(());
+        }
 
-        fs::write(output_dir.join(self.config.get_rss_filename()), rss_xml)?;
+        let rss_xml = self.render_page("rss", path, &self.create_rss_data(posts, feed))?;
+        fs::write(path, rss_xml)?;
 
         Ok(())
     }
 
-    fn generate_simple_rss(&self) -> Result<String> {
+    fn create_rss_data(&self, posts: &[&Post], feed: &Value) -> Value {
+        let rss_posts: Vec<Value> = posts
+            .iter()
+            .take(self.config.get_rss_limit())
+            .map(|post| {
+                json!({
+                    "title": post.front_matter.title,
+                    "link": format!("{}{}", self.config.site.base_url, post.url),
+                    "description": post.description(),
+                    "content": post.html_content,
+                    "pub_date": post.front_matter.date,
+                    "author": self.get_post_author_names(post).join(", "),
+                    "extra": post.front_matter.extra
+                })
+            })
+            .collect();
+
+        json!({
+            "site": self.get_site_context(),
+            "feed": feed,
+            "posts": rss_posts,
+            "build_date": chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S %z").to_string()
+        })
+    }
+
+    fn generate_simple_rss(&self, posts: &[&Post], feed: &Value) -> String {
         let mut rss = String::new();
         rss.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
         rss.push_str("\n");
         rss.push_str(r#"<rss version="2.0">"#);
         rss.push_str("\n<channel>");
-        rss.push_str(&format!("\n<title>{}</title>", self.config.site.title));
+        rss.push_str(&format!(
+            "\n<title>{}</title>",
+            feed["title"].as_str().unwrap_or_default()
+        ));
         rss.push_str(&format!(
             "\n<description>{}</description>",
-            self.config.site.description
+            feed["description"].as_str().unwrap_or_default()
+        ));
+        rss.push_str(&format!(
+            "\n<link>{}</link>",
+            feed["link"].as_str().unwrap_or_default()
         ));
-        rss.push_str(&format!("\n<link>{}</link>", self.config.site.base_url));
         rss.push_str(&format!(
             "\n<lastBuildDate>{}</lastBuildDate>",
             chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S %z")
         ));
 
-        for post in self.posts.iter().take(self.config.get_rss_limit()) {
+        for post in posts.iter().take(self.config.get_rss_limit()) {
             rss.push_str("\n<item>");
             rss.push_str(&format!("\n<title>{}</title>", post.front_matter.title));
             rss.push_str(&format!(
-                "\n<link>{}/posts/{}</link>",
-                self.config.site.base_url, post.slug
+                "\n<link>{}{}</link>",
+                self.config.site.base_url, post.url
             ));
             rss.push_str(&format!(
                 "\n<description>{}</description>",
-                post.front_matter
-                    .description
-                    .as_ref()
-                    .unwrap_or(&post.front_matter.title)
+                post.description()
             ));
             rss.push_str(&format!("\n<pubDate>{}</pubDate>", post.front_matter.date));
             rss.push_str("\n</item>");
@@ -526,37 +1384,35 @@ impl SiteGenerator {
         rss.push_str("\n</channel>");
         rss.push_str("\n</rss>");
 
-        Ok(rss)
+        rss
     }
 
     fn generate_about_page(&self) -> Result<()> {
-        let output_dir = Path::new(&self.config.build.output_dir);
-
         // Check about template
         if !self.has_template("about") {
             println!("⚠️  Template 'about' not found, skipping about page generation");
             return Ok(());
         }
 
-        let context = json!({
+        let path = self.output_dir().join("about.html");
+        let html = self.render_page("about", &path, &self.create_about_data())?;
+        fs::write(path, html)?;
+
+        Ok(())
+    }
+
+    fn create_about_data(&self) -> Value {
+        json!({
             "site": self.get_site_context(),
             "recent_posts": self.get_recent_posts(self.config.get_recent_posts_limit()),
             "popular_tags": self.get_popular_tags(),
             "page": {
-                "title": "About",
-                "description": "About this blog",
-                "url": "/about"
+                "title": self.t("about.title", &[]),
+                "description": self.t("about.description", &[]),
+                "url": self.url("/about")
             },
             "is_about": true
-        });
-
-        let html = self.handlebars.render("about", &context).map_err(|e| {
-            ZahuyachError::InvalidInput(format!("About template render error: {}", e))
-        })?;
-
-        fs::write(output_dir.join("about.html"), html)?;
-
-        Ok(())
+        })
     }
 
     fn generate_404_page(&self) -> Result<()> {
@@ -568,39 +1424,50 @@ impl SiteGenerator {
             return Ok(());
         }
 
-        let context = json!({
+        let path = output_dir.join("404.html");
+        let html = self.render_page("404", &path, &self.create_not_found_data())?;
+        fs::write(path, html)?;
+
+        Ok(())
+    }
+
+    fn create_not_found_data(&self) -> Value {
+        json!({
             "site": self.get_site_context(),
             "recent_posts": self.get_recent_posts(self.config.get_recent_posts_limit()),
             "popular_tags": self.get_popular_tags(),
             "page": {
-                "title": "404 - Page Not Found",
-                "description": "The page you're looking for doesn't exist",
+                "title": self.t("not_found.title", &[]),
+                "description": self.t("not_found.description", &[]),
                 "url": "/404.html"
             }
-        });
-
-        let html = self.handlebars.render("404", &context).map_err(|e| {
-            ZahuyachError::InvalidInput(format!("404 template render error: {}", e))
-        })?;
-
-        fs::write(output_dir.join("404.html"), html)?;
-
-        Ok(())
+        })
     }
 
+    /// Copies static files of the embedded basic theme, the selected theme
+    /// and the project, later ones overwriting earlier ones.
     fn copy_static_files(&self) -> Result<()> {
-        let static_dir = Path::new(&self.config.build.static_dir);
         let output_static_dir = Path::new(&self.config.build.output_dir).join("static");
 
-        if !static_dir.exists() {
-            println!(
-                "⚠️  Static directory '{}' does not exist, skipping",
-                static_dir.display()
-            );
-            return Ok(());
+        for (relative, data) in templates::embedded_files(templates::STATIC_DIR) {
+            let target = output_static_dir.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target, data)?;
         }
 
-        self.copy_dir_recursive(static_dir, &output_static_dir)?;
+        if let Some(theme_dir) = self.theme_dir()? {
+            let theme_static_dir = theme_dir.join(templates::STATIC_DIR);
+            if theme_static_dir.is_dir() {
+                self.copy_dir_recursive(&theme_static_dir, &output_static_dir)?;
+            }
+        }
+
+        let static_dir = Path::new(&self.config.build.static_dir);
+        if static_dir.exists() {
+            self.copy_dir_recursive(static_dir, &output_static_dir)?;
+        }
 
         Ok(())
     }
@@ -625,8 +1492,20 @@ impl SiteGenerator {
 
     // Helper methods for creating template contexts
 
-    fn create_post_data(&self, post: &Post) -> Value {
-        json!({
+    fn create_post_data(
+        &self,
+        post: &Post,
+        all_series: &BTreeMap<String, Vec<&Post>>,
+        all_translations: &HashMap<String, Vec<&Post>>,
+    ) -> Value {
+        let (translations, hreflang) = self.get_translations_context(post, all_translations);
+        let authors = self.get_post_authors(post);
+        // `image` стал полем front matter, но темы могли читать его из `extra`
+        let mut extra = post.front_matter.extra.clone();
+        if let Some(image) = &post.front_matter.image {
+            extra.insert("image".to_string(), json!(image));
+        }
+        let mut data = json!({
             "site": self.get_site_context(),
             "post": {
                 "title": post.front_matter.title,
@@ -636,53 +1515,409 @@ impl SiteGenerator {
                 "date_formatted": self.format_date(&post.front_matter.date),
                 "date_short": self.format_date_short(&post.front_matter.date),
                 "date_iso": post.front_matter.date,
-                "author": post.front_matter.author.as_ref().unwrap_or(&self.config.site.author),
+                "author": self.get_post_author_names(post).join(", "),
+                "author_info": authors.first(),
+                "authors": authors,
                 "tags": post.front_matter.tags.as_ref().unwrap_or(&vec![]),
                 "categories": post.front_matter.categories.as_ref().unwrap_or(&vec![]),
-                "description": post.front_matter.description.as_ref().unwrap_or(&post.front_matter.title),
+                "description": post.description(),
+                "summary": post.summary,
+                "excerpt": post.excerpt,
                 "slug": post.slug,
                 "reading_time": self.calculate_reading_time(&post.content),
-                "word_count": post.content.split_whitespace().count()
+                "word_count": post.content.split_whitespace().count(),
+                "toc": post.toc,
+                "has_toc": !post.toc.is_empty(),
+                "url": post.url,
+                "lang": self.post_language(post),
+                "translations": translations,
+                "hreflang": hreflang,
+                "image": post.image(),
+                "extra": extra
             },
+            "series": post.front_matter.series.as_ref()
+                .filter(|_| self.config.is_series_enabled())
+                .map(|name| {
+                    let parts = all_series.get(name).map(Vec::as_slice).unwrap_or_default();
+                    self.get_series_context(name, parts, Some(post))
+                }),
             "categories": self.get_categories_tree(),
             "popular_tags": self.get_popular_tags(),
             "recent_posts": self.get_recent_posts(self.config.get_recent_posts_limit()),
             "page": {
                 "title": post.front_matter.title,
-                "description": post.front_matter.description.as_ref().unwrap_or(&post.front_matter.title),
-                "url": format!("/posts/{}", post.slug)
+                "description": post.description(),
+                "url": post.url
             }
+        });
+        if self.config.is_seo_enabled() {
+            data["seo"] = self.get_seo_context(&data["page"], Some(post));
+        }
+        data
+    }
+
+    /// Builds the `seo` context of a page: canonical URL, Open Graph and
+    /// Twitter card fields and JSON-LD objects. The page of a `post` gets
+    /// `article` fields, a `BlogPosting` and its front matter `image`.
+    fn get_seo_context(&self, page: &Value, post: Option<&Post>) -> Value {
+        let site = self.config.site_for_language(&self.language);
+        let url = page["url"].as_str().unwrap_or("/");
+        let text = |value: &Value, fallback: &str| {
+            value
+                .as_str()
+                .filter(|text| !text.is_empty())
+                .unwrap_or(fallback)
+                .to_string()
+        };
+
+        // Главная называется именем сайта, а не «Главная»
+        let title = if url == self.url("/") {
+            site.title.clone()
+        } else {
+            text(&page["title"], &site.title)
+        };
+        let description = text(&page["description"], &site.description);
+        let canonical = self.absolute_url(url);
+        let image = post
+            .and_then(Post::image)
+            .map(|image| self.absolute_url(&image));
+
+        let mut json_ld = vec![json!({
+            "@context": "https://schema.org",
+            "@type": "WebSite",
+            "name": site.title,
+            "description": site.description,
+            "url": self.absolute_url(&self.url("/")),
+            "inLanguage": self.language
+        })];
+        if let Some(post) = post {
+            json_ld.push(self.get_blog_posting_ld(post, &description, image.as_deref()));
+        }
+        json_ld.extend(self.get_breadcrumbs_ld(url, &title));
+
+        json!({
+            "canonical": canonical,
+            "title": title,
+            "description": description,
+            "image": image,
+            "open_graph": {
+                "type": if post.is_some() { "article" } else { "website" },
+                "title": title,
+                "description": description,
+                "url": canonical,
+                "site_name": site.title,
+                "locale": self.language.replace('-', "_"),
+                "image": image,
+                "article": post.map(|post| json!({
+                    "published_time": post.front_matter.date,
+                    "modified_time": post.front_matter.extra::<String>("updated"),
+                    "authors": self.get_post_author_names(post),
+                    "tags": post.front_matter.tags.as_ref().unwrap_or(&vec![])
+                }))
+            },
+            "twitter": {
+                "card": if image.is_some() { "summary_large_image" } else { "summary" },
+                "title": title,
+                "description": description,
+                "image": image,
+                "site": site.social.as_ref()
+                    .and_then(|social| social.twitter.as_deref())
+                    .and_then(twitter_handle)
+            },
+            "json_ld": json_ld
         })
     }
 
+    fn get_blog_posting_ld(&self, post: &Post, description: &str, image: Option<&str>) -> Value {
+        let date = &post.front_matter.date;
+        let authors: Vec<Value> = self
+            .get_post_authors(post)
+            .iter()
+            .map(|author| {
+                json!({
+                    "@type": "Person",
+                    "name": author["name"],
+                    "url": self.absolute_url(author["url"].as_str().unwrap_or("/"))
+                })
+            })
+            .collect();
+        let site = self.config.site_for_language(&self.language);
+
+        let mut posting = json!({
+            "@context": "https://schema.org",
+            "@type": "BlogPosting",
+            "headline": post.front_matter.title,
+            "description": description,
+            "url": self.absolute_url(&post.url),
+            "mainEntityOfPage": {
+                "@type": "WebPage",
+                "@id": self.absolute_url(&post.url)
+            },
+            "datePublished": date,
+            "dateModified": post.front_matter.extra::<String>("updated").unwrap_or_else(|| date.clone()),
+            "inLanguage": self.post_language(post),
+            "author": authors,
+            "publisher": {
+                "@type": "Person",
+                "name": self.get_author_context(&site.author)["name"]
+            },
+            "keywords": post.front_matter.tags.as_ref().unwrap_or(&vec![])
+        });
+        if let Some(image) = image {
+            posting["image"] = json!(image);
+        }
+        posting
+    }
+
+    /// Builds a `BreadcrumbList` from the sections of `url`: home, the
+    /// listing page of tags, series or authors, then the page itself. The
+    /// home page has none.
+    fn get_breadcrumbs_ld(&self, url: &str, title: &str) -> Option<Value> {
+        let prefix = self.language_prefix(&self.language);
+        let path = url.strip_prefix(prefix.as_str()).unwrap_or(url);
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+        let section = segments.next()?;
+
+        let mut crumbs = vec![(self.t("nav.home", &[]), self.url("/"))];
+        // Разделы, у которых есть своя страница со списком
+        let listing = match section {
+            "tags" => Some(("tags.title", "/tags")),
+            "series" => Some(("series_list.title", "/series/")),
+            "authors" => Some(("authors.title", "/authors/")),
+            _ => None,
+        };
+        if let Some((key, path)) = listing.filter(|_| segments.next().is_some()) {
+            crumbs.push((self.t(key, &[]), self.url(path)));
+        }
+        crumbs.push((title.to_string(), url.to_string()));
+
+        let items: Vec<Value> = crumbs
+            .iter()
+            .enumerate()
+            .map(|(index, (name, url))| {
+                json!({
+                    "@type": "ListItem",
+                    "position": index + 1,
+                    "name": name,
+                    "item": self.absolute_url(url)
+                })
+            })
+            .collect();
+        Some(json!({
+            "@context": "https://schema.org",
+            "@type": "BreadcrumbList",
+            "itemListElement": items
+        }))
+    }
+
+    /// Absolute URL of a site path; external URLs are kept.
+    fn absolute_url(&self, path: &str) -> String {
+        helpers::join_url(self.config.site.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Translated page string in the current language, see
+    /// [`Translations::translate`].
+    fn t(&self, key: &str, args: &[(&str, &str)]) -> String {
+        self.translations
+            .iter()
+            .find(|translations| translations.language() == self.language)
+            .map_or_else(
+                || key.to_string(),
+                |translations| translations.translate(key, None, args),
+            )
+    }
+
+    /// Posts of the current language.
+    fn posts(&self) -> impl Iterator<Item = &Post> {
+        self.posts
+            .iter()
+            .filter(|post| self.post_language(post) == self.language)
+    }
+
+    /// Returns the site language of a post: its `lang` if the site has that
+    /// language, the default language otherwise.
+    fn post_language<'a>(&'a self, post: &'a Post) -> &'a str {
+        post.front_matter
+            .lang
+            .as_deref()
+            .filter(|lang| self.config.get_languages().contains(lang))
+            .unwrap_or_else(|| self.config.get_language())
+    }
+
+    /// URL prefix of a language: empty for the default one, `/<lang>` for
+    /// the others.
+    fn language_prefix(&self, language: &str) -> String {
+        if language == self.config.get_language() {
+            String::new()
+        } else {
+            format!("/{language}")
+        }
+    }
+
+    /// Site path in the current language.
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.language_prefix(&self.language))
+    }
+
+    /// Output directory of the current language.
+    fn output_dir(&self) -> PathBuf {
+        Path::new(&self.config.build.output_dir)
+            .join(self.language_prefix(&self.language).trim_start_matches('/'))
+    }
+
+    /// Returns the key linking translations of a post: front matter
+    /// `translation_key`, or the content path without the language tree,
+    /// so `content/en/hello.md` translates `content/hello.md`.
+    fn translation_key(&self, post: &Post) -> String {
+        if let Some(key) = &post.front_matter.translation_key {
+            return key.clone();
+        }
+
+        let source = post
+            .bundle_dir
+            .clone()
+            .unwrap_or_else(|| post.file_path.with_extension(""));
+        let relative = source
+            .strip_prefix(&self.config.build.content_dir)
+            .unwrap_or(&source);
+        let mut parts: Vec<&str> = relative.iter().filter_map(|part| part.to_str()).collect();
+        if self.tree_language(&source).is_some() {
+            parts.remove(0);
+        }
+        parts.join("/")
+    }
+
+    /// Groups posts of every language by translation key, each group in
+    /// site language order. Empty for single-language sites.
+    fn collect_translations(&self) -> HashMap<String, Vec<&Post>> {
+        let mut all_translations: HashMap<String, Vec<&Post>> = HashMap::new();
+        if !self.config.is_multilingual() {
+            return all_translations;
+        }
+
+        for post in &self.posts {
+            all_translations
+                .entry(self.translation_key(post))
+                .or_default()
+                .push(post);
+        }
+
+        let languages = self.config.get_languages();
+        for versions in all_translations.values_mut() {
+            versions.sort_by_key(|version| {
+                languages
+                    .iter()
+                    .position(|lang| *lang == self.post_language(version))
+            });
+        }
+        all_translations
+    }
+
+    /// Builds `translations` (links to the other versions) and `hreflang`
+    /// (absolute URLs of all versions plus `x-default`) for a post, using
+    /// the groups from [`Self::collect_translations`].
+    fn get_translations_context(
+        &self,
+        post: &Post,
+        all_translations: &HashMap<String, Vec<&Post>>,
+    ) -> (Vec<Value>, Vec<Value>) {
+        let translations: Vec<&Post> = all_translations
+            .get(&self.translation_key(post))
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|other| other.url != post.url)
+            .collect();
+        if translations.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+
+        let links = translations
+            .iter()
+            .map(|other| {
+                let lang = self.post_language(other);
+                json!({
+                    "lang": lang,
+                    "name": self.config.get_language_name(lang),
+                    "title": other.front_matter.title,
+                    "url": other.url
+                })
+            })
+            .collect();
+
+        let base_url = &self.config.site.base_url;
+        let mut hreflang: Vec<Value> = std::iter::once(post)
+            .chain(translations.iter().copied())
+            .map(|version| {
+                json!({
+                    "lang": self.post_language(version),
+                    "url": format!("{base_url}{}", version.url)
+                })
+            })
+            .collect();
+        if let Some(default) = std::iter::once(post)
+            .chain(translations)
+            .find(|version| self.post_language(version) == self.config.get_language())
+        {
+            hreflang.push(json!({
+                "lang": "x-default",
+                "url": format!("{base_url}{}", default.url)
+            }));
+        }
+
+        (links, hreflang)
+    }
+
+    fn get_languages_context(&self) -> Vec<Value> {
+        self.config
+            .get_languages()
+            .into_iter()
+            .map(|lang| {
+                json!({
+                    "code": lang,
+                    "name": self.config.get_language_name(lang),
+                    "url": format!("{}/", self.language_prefix(lang)),
+                    "is_current": lang == self.language
+                })
+            })
+            .collect()
+    }
+
     fn get_site_context(&self) -> Value {
+        let site = self.config.site_for_language(&self.language);
         json!({
-            "title": self.config.site.title,
-            "description": self.config.site.description,
-            "author": self.config.site.author,
-            "base_url": self.config.site.base_url,
-            "language": self.config.site.language.as_ref().unwrap_or(&"ru".to_string()),
+            "title": site.title,
+            "description": site.description,
+            "author": self.get_author_context(&site.author),
+            "base_url": site.base_url,
+            "language": self.language,
+            "lang_prefix": self.language_prefix(&self.language),
+            "languages": self.get_languages_context(),
             "current_year": chrono::Utc::now().year(),
-            "email": self.config.site.email.as_ref().unwrap_or(&String::new()),
-            "timezone": self.config.site.timezone.as_ref().unwrap_or(&"UTC".to_string()),
-            "social": self.config.site.social.as_ref().map(|s| json!({
-                "github": s.github.as_ref().unwrap_or(&String::new()),
-                "twitter": s.twitter.as_ref().unwrap_or(&String::new()),
-                "linkedin": s.linkedin.as_ref().unwrap_or(&String::new()),
-                "email": s.email.as_ref().or(self.config.site.email.as_ref()).unwrap_or(&String::new()),
-                "mastodon": s.mastodon.as_ref().unwrap_or(&String::new()),
-                "youtube": s.youtube.as_ref().unwrap_or(&String::new()),
-                "instagram": s.instagram.as_ref().unwrap_or(&String::new()),
-                "facebook": s.facebook.as_ref().unwrap_or(&String::new())
-            })).unwrap_or_else(|| json!({
+            "email": site.email.as_deref().unwrap_or_default(),
+            "timezone": site.timezone.as_deref().unwrap_or("UTC"),
+            "features": {
+                "code_copy": self.config.is_code_copy_enabled(),
+                "seo_optimized": self.config.is_seo_enabled()
+            },
+            "social": site.social.as_ref().map_or_else(|| json!({
                 "github": "",
                 "twitter": "",
                 "linkedin": "",
-                "email": self.config.site.email.as_ref().unwrap_or(&String::new()),
+                "email": site.email.as_deref().unwrap_or_default(),
                 "mastodon": "",
                 "youtube": "",
                 "instagram": "",
                 "facebook": ""
+            }), |s| json!({
+                "github": s.github.as_deref().unwrap_or_default(),
+                "twitter": s.twitter.as_deref().unwrap_or_default(),
+                "linkedin": s.linkedin.as_deref().unwrap_or_default(),
+                "email": s.email.as_deref().or(site.email.as_deref()).unwrap_or_default(),
+                "mastodon": s.mastodon.as_deref().unwrap_or_default(),
+                "youtube": s.youtube.as_deref().unwrap_or_default(),
+                "instagram": s.instagram.as_deref().unwrap_or_default(),
+                "facebook": s.facebook.as_deref().unwrap_or_default()
             }))
         })
     }
@@ -694,32 +1929,40 @@ impl SiteGenerator {
             &posts
         };
 
-        posts_to_show.iter().map(|post| {
-            json!({
-                "title": post.front_matter.title,
-                "slug": post.slug,
-                "url": format!("/posts/{}", post.slug),
-                "date": self.format_date(&post.front_matter.date),
-                "date_raw": post.front_matter.date,
-                "date_formatted": self.format_date(&post.front_matter.date),
-                "date_short": self.format_date_short(&post.front_matter.date),
-                "date_iso": post.front_matter.date,
-                "author": post.front_matter.author.as_ref().unwrap_or(&self.config.site.author),
-                "tags": post.front_matter.tags.as_ref().unwrap_or(&vec![]),
-                "categories": post.front_matter.categories.as_ref().unwrap_or(&vec![]),
-                "description": post.front_matter.description.as_ref().unwrap_or(&post.front_matter.title),
-                "excerpt": self.create_excerpt(&post.content),
-                "reading_time": self.calculate_reading_time(&post.content),
-                "featured": post.front_matter.featured.unwrap_or(false),
-                "permalink": format!("{}/posts/{}", self.config.site.base_url, post.slug)
+        posts_to_show
+            .iter()
+            .map(|post| {
+                let authors = self.get_post_authors(post);
+                json!({
+                    "title": post.front_matter.title,
+                    "slug": post.slug,
+                    "url": post.url,
+                    "date": self.format_date(&post.front_matter.date),
+                    "date_raw": post.front_matter.date,
+                    "date_formatted": self.format_date(&post.front_matter.date),
+                    "date_short": self.format_date_short(&post.front_matter.date),
+                    "date_iso": post.front_matter.date,
+                    "author": self.get_post_author_names(post).join(", "),
+                    "author_info": authors.first(),
+                    "authors": authors,
+                    "tags": post.front_matter.tags.as_ref().unwrap_or(&vec![]),
+                    "categories": post.front_matter.categories.as_ref().unwrap_or(&vec![]),
+                    "description": post.description(),
+                    "excerpt": post.excerpt,
+                    "summary": post.summary,
+                    "reading_time": self.calculate_reading_time(&post.content),
+                    "featured": post.front_matter.featured.unwrap_or(false),
+                    "permalink": format!("{}{}", self.config.site.base_url, post.url),
+                    "extra": post.front_matter.extra
+                })
             })
-        }).collect()
+            .collect()
     }
 
     fn get_popular_tags(&self) -> Vec<Value> {
         let mut tag_counts: HashMap<String, usize> = HashMap::new();
 
-        for post in &self.posts {
+        for post in self.posts() {
             if let Some(tags) = &post.front_matter.tags {
                 for tag in tags {
                     *tag_counts.entry(tag.clone()).or_insert(0) += 1;
@@ -743,10 +1986,160 @@ impl SiteGenerator {
             .collect()
     }
 
+    /// Returns the author ids of a post: front matter `authors`, then
+    /// `author`, then the site author. Ids without a profile in `[authors]`
+    /// are treated as plain names.
+    fn get_post_author_ids<'a>(&'a self, post: &'a Post) -> Vec<&'a str> {
+        match (&post.front_matter.authors, &post.front_matter.author) {
+            (Some(authors), _) if !authors.is_empty() => {
+                authors.iter().map(String::as_str).collect()
+            }
+            (_, Some(author)) => vec![author.as_str()],
+            _ => vec![self.config.site.author.as_str()],
+        }
+    }
+
+    fn get_post_author_names(&self, post: &Post) -> Vec<String> {
+        self.get_post_author_ids(post)
+            .into_iter()
+            .map(|id| {
+                self.config
+                    .get_author(id)
+                    .map_or_else(|| id.to_string(), |author| author.name.clone())
+            })
+            .collect()
+    }
+
+    fn get_post_authors(&self, post: &Post) -> Vec<Value> {
+        self.get_post_author_ids(post)
+            .into_iter()
+            .map(|id| self.get_author_context(id))
+            .collect()
+    }
+
+    /// Builds the author profile context for an `[authors]` id, or a
+    /// name-only profile for authors that aren't registered. `id` is always
+    /// the value used in front matter, `slug` its URL form.
+    fn get_author_context(&self, id: &str) -> Value {
+        let slug = self.slugify(id);
+        let Some(author) = self.config.get_author(id) else {
+            return json!({
+                "id": id,
+                "slug": slug,
+                "name": id,
+                "url": self.url(&format!("/authors/{slug}/")),
+                "links": []
+            });
+        };
+
+        let links: Vec<Value> = author
+            .social
+            .as_ref()
+            .map(|social| {
+                [
+                    ("GitHub", &social.github),
+                    ("Twitter", &social.twitter),
+                    ("LinkedIn", &social.linkedin),
+                    ("Mastodon", &social.mastodon),
+                    ("YouTube", &social.youtube),
+                    ("Instagram", &social.instagram),
+                    ("Facebook", &social.facebook),
+                ]
+                .into_iter()
+                .filter_map(|(name, url)| {
+                    url.as_ref().map(|url| json!({ "name": name, "url": url }))
+                })
+                .collect()
+            })
+            .unwrap_or_default();
+
+        json!({
+            "id": id,
+            "slug": slug,
+            "name": author.name,
+            "title": author.title,
+            "bio": author.bio,
+            "avatar": author.avatar,
+            "email": author.email,
+            "website": author.url,
+            "url": self.url(&format!("/authors/{slug}/")),
+            "links": links
+        })
+    }
+
+    /// Groups posts by author id. Registered authors are always present,
+    /// even without posts.
+    fn collect_authors(&self) -> BTreeMap<String, Vec<&Post>> {
+        let mut all_authors: BTreeMap<String, Vec<&Post>> = self
+            .config
+            .authors
+            .iter()
+            .flat_map(|authors| authors.keys())
+            .map(|id| (id.clone(), Vec::new()))
+            .collect();
+
+        for post in self.posts() {
+            for id in self.get_post_author_ids(post) {
+                all_authors.entry(id.to_string()).or_default().push(post);
+            }
+        }
+
+        all_authors
+    }
+
+    /// Groups posts by series name. Parts are ordered by `series_order`,
+    /// then by date; parts without an explicit order come last.
+    fn collect_series(&self) -> BTreeMap<String, Vec<&Post>> {
+        let mut all_series: BTreeMap<String, Vec<&Post>> = BTreeMap::new();
+
+        for post in self.posts() {
+            if let Some(series) = &post.front_matter.series {
+                all_series.entry(series.clone()).or_default().push(post);
+            }
+        }
+
+        for posts in all_series.values_mut() {
+            posts.sort_by(|a, b| {
+                let order_a = a.front_matter.series_order.unwrap_or(usize::MAX);
+                let order_b = b.front_matter.series_order.unwrap_or(usize::MAX);
+                order_a
+                    .cmp(&order_b)
+                    .then_with(|| a.front_matter.date.cmp(&b.front_matter.date))
+            });
+        }
+
+        all_series
+    }
+
+    /// Builds the `series` context from its ordered `parts` (see
+    /// [`Self::collect_series`]): every part with its 1-based position, plus
+    /// the position of `current` when rendering one of the parts.
+    fn get_series_context(&self, name: &str, parts: &[&Post], current: Option<&Post>) -> Value {
+        let slug = self.slugify(name);
+
+        let current_index = current
+            .and_then(|current| parts.iter().position(|p| p.slug == current.slug))
+            .map(|index| index + 1);
+
+        json!({
+            "name": name,
+            "slug": slug,
+            "url": self.url(&format!("/series/{slug}/")),
+            "total": parts.len(),
+            "current": current_index,
+            "parts": parts.iter().enumerate().map(|(index, part)| json!({
+                "title": part.front_matter.title,
+                "url": part.url,
+                "position": index + 1,
+                "is_current": current_index == Some(index + 1)
+            })).collect::<Vec<_>>()
+        })
+    }
+
     fn get_categories_tree(&self) -> Vec<Value> {
         let mut category_counts: HashMap<String, usize> = HashMap::new();
 
-        for post in &self.posts {
+        for post in self.posts() {
             if let Some(categories) = &post.front_matter.categories {
                 for category in categories {
                     *category_counts.entry(category.clone()).or_insert(0) += 1;
@@ -770,14 +2163,13 @@ impl SiteGenerator {
     }
 
     fn get_recent_posts(&self, limit: usize) -> Vec<Value> {
-        self.posts
-            .iter()
+        self.posts()
             .take(limit)
             .map(|post| {
                 json!({
                     "title": post.front_matter.title,
                     "slug": post.slug,
-                    "url": format!("/posts/{}", post.slug),
+                    "url": post.url,
                     "date": self.format_date(&post.front_matter.date),
                     "date_short": self.format_date_short(&post.front_matter.date),
                     "date_iso": post.front_matter.date,
@@ -789,21 +2181,19 @@ impl SiteGenerator {
 
     fn get_site_stats(&self) -> Value {
         let total_words: usize = self
-            .posts
-            .iter()
+            .posts()
             .map(|post| post.content.split_whitespace().count())
             .sum();
 
         // Исправлено: собираем категории в owned значения
         let all_categories: HashSet<String> = self
-            .posts
-            .iter()
+            .posts()
             .filter_map(|post| post.front_matter.categories.as_ref())
             .flat_map(|categories| categories.iter().cloned())
             .collect();
 
         json!({
-            "total_posts": self.posts.len(),
+            "total_posts": self.posts().count(),
             "total_words": total_words,
             "total_categories": all_categories.len(),
             "last_updated": chrono::Utc::now().format("%Y-%m-%d").to_string()
@@ -813,18 +2203,7 @@ impl SiteGenerator {
     // Utility methods
 
     fn slugify(&self, text: &str) -> String {
-        text.to_lowercase()
-            .chars()
-            .map(|c| match c {
-                'а'..='я' | 'ё' => c,
-                'a'..='z' | '0'..='9' => c,
-                _ => '-',
-            })
-            .collect::<String>()
-            .split('-')
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>()
-            .join("-")
+        markdown::slugify(text)
     }
 
     fn format_date(&self, date: &str) -> String {
@@ -886,53 +2265,822 @@ impl SiteGenerator {
         let words = content.split_whitespace().count();
         (words / self.config.get_reading_speed()).max(1)
     }
+}
 
-    fn create_excerpt(&self, content: &str) -> String {
-        use pulldown_cmark::{Parser, html};
+impl ShortcodeRenderer for SiteGenerator {
+    fn render_shortcode(&self, name: &str, context: &Value) -> Option<Result<String>> {
+        let template = format!("{SHORTCODES_DIR}/{name}");
+        self.has_template(&template).then(|| {
+            self.render(&template, context).map_err(|e| {
+                let context = self.with_data(context);
+                let error = describe_render_error(&e, &template, None, &context);
+                ZahuyachError::InvalidInput(format!("Shortcode '{name}' failed to render: {error}"))
+            })
+        })
+    }
+}
 
-        // Разделяем контент на строки
-        let lines: Vec<&str> = content.lines().collect();
+/// Turns a Twitter profile URL (or handle) into `@handle`.
+fn twitter_handle(profile: &str) -> Option<String> {
+    let path = match profile.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        None => profile,
+    };
+    let handle = path.split(['/', '?']).next()?.trim_start_matches('@');
+    (!handle.is_empty()).then(|| format!("@{handle}"))
+}
 
-        // Ищем маркер excerpt или берем первые несколько абзацев
-        let excerpt_separator = self.config.get_excerpt_separator();
-        let excerpt_content = if let Some(more_index) = lines
-            .iter()
-            .position(|line| line.trim() == excerpt_separator)
-        {
-            lines[..more_index].join("\n")
-        } else {
-            // Берем первые N непустых абзаца
-            let mut paragraphs = Vec::new();
-            let mut current_paragraph = Vec::new();
-
-            for line in lines.iter() {
-                if line.trim().is_empty() {
-                    if !current_paragraph.is_empty() {
-                        paragraphs.push(current_paragraph.join("\n"));
-                        current_paragraph.clear();
-                    }
-                } else {
-                    current_paragraph.push(*line);
-                }
+/// Describes a render error with its template, line and page. Missing
+/// variables also list the keys of the closest object on their path.
+fn describe_render_error(
+    error: &RenderError,
+    name: &str,
+    page: Option<&Path>,
+    context: &Value,
+) -> String {
+    let template = error.template_name.as_deref().unwrap_or(name);
+    let mut message = format!("Template render error in '{template}'");
+    if let Some(line) = error.line_no {
+        let _ = write!(message, " at line {line}");
+        if let Some(column) = error.column_no {
+            let _ = write!(message, ", column {column}");
+        }
+    }
+    if let Some(page) = page {
+        let _ = write!(message, " while building '{}'", page.display());
+    }
 
-                if paragraphs.len() >= self.config.get_excerpt_length() {
-                    break;
-                }
+    match error.reason() {
+        RenderErrorReason::MissingVariable(Some(path)) => {
+            let (scope, keys) = available_keys(context, path);
+            let scope = if scope.is_empty() {
+                "top-level keys".to_string()
+            } else {
+                format!("keys of '{scope}'")
+            };
+            let _ = write!(
+                message,
+                ": missing variable '{path}' (available {scope}: {})",
+                keys.join(", ")
+            );
+            // Внутри #each и #with пути считаются от текущего элемента
+            if !path.starts_with('@')
+                && context
+                    .pointer(&format!("/{}", path.replace('.', "/")))
+                    .is_some()
+            {
+                let _ = write!(
+                    message,
+                    "; it exists at the top level, use '@root.{path}' inside blocks"
+                );
             }
+        }
+        reason => {
+            let _ = write!(message, ": {reason}");
+        }
+    }
+    message
+}
 
-            if !current_paragraph.is_empty() && paragraphs.len() < self.config.get_excerpt_length()
-            {
-                paragraphs.push(current_paragraph.join("\n"));
+/// Follows `path` through `context` as far as it exists and returns the
+/// path of the last object reached with its keys.
+fn available_keys<'a>(context: &'a Value, path: &str) -> (String, Vec<&'a str>) {
+    let segments: Vec<&str> = path
+        .split(['.', '/'])
+        .map(|segment| segment.trim_start_matches('[').trim_end_matches(']'))
+        .filter(|segment| !matches!(*segment, "" | "this" | "@root" | ".."))
+        .collect();
+
+    let mut scope = Vec::new();
+    let mut value = context;
+    for segment in segments.iter().take(segments.len().saturating_sub(1)) {
+        let next = match value {
+            Value::Array(items) => segment.parse().ok().and_then(|i: usize| items.get(i)),
+            _ => value.get(segment),
+        };
+        match next {
+            Some(next) if next.is_object() || next.is_array() => {
+                scope.push(*segment);
+                value = next;
             }
+            _ => break,
+        }
+    }
+
+    // У массива показываем ключи первого элемента
+    let object = match value {
+        Value::Array(items) => items.first().unwrap_or(value),
+        _ => value,
+    };
+    let keys = object
+        .as_object()
+        .map(|map| map.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    (scope.join("."), keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn generator(templates_dir: &Path) -> SiteGenerator {
+        generator_with(templates_dir, "")
+    }
+
+    fn generator_with(templates_dir: &Path, build: &str) -> SiteGenerator {
+        let config: Config = toml::from_str(&format!(
+            r#"
+[site]
+title = "Test"
+description = "Test"
+author = "Tester"
+base_url = "https://example.com"
+
+[build]
+output_dir = "dist"
+content_dir = "content"
+static_dir = "static"
+templates_dir = "{}"
+{build}
+"#,
+            templates_dir.display()
+        ))
+        .unwrap();
+        SiteGenerator::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_templates_registered_by_relative_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let templates = temp_dir.path();
+        fs::create_dir_all(templates.join("partials")).unwrap();
+        fs::write(templates.join("header.html"), "top").unwrap();
+        fs::write(templates.join("partials/header.hbs"), "partial").unwrap();
+        fs::write(templates.join("post.html"), "{{> partials/header}}").unwrap();
+        fs::write(templates.join("notes.txt"), "not a template").unwrap();
+
+        let mut generator = generator(templates);
+        generator.load_templates().unwrap();
+
+        assert!(generator.has_template("header"));
+        assert!(generator.has_template("partials/header"));
+        assert!(!generator.has_template("notes"));
+        assert_eq!(generator.render("post", &json!({})).unwrap(), "partial");
+    }
+
+    #[test]
+    fn test_strict_templates() {
+        let temp_dir = TempDir::new().unwrap();
+        let templates = temp_dir.path();
+        fs::write(
+            templates.join("post.html"),
+            "<h1>{{post.title}}</h1>\n{{post.tittle}}",
+        )
+        .unwrap();
+        fs::write(
+            templates.join("tag.html"),
+            "{{#each posts}}{{this.title}}{{site.title}}{{/each}}",
+        )
+        .unwrap();
+        let context = json!({
+            "site": {"title": "Blog"},
+            "post": {"title": "Hello", "slug": "hello"},
+            "posts": [{"title": "Hello"}]
+        });
+        let page = Path::new("dist/posts/hello.html");
+
+        // Без строгого режима опечатка выводится пустой строкой
+        let mut generator = generator(templates);
+        generator.load_templates().unwrap();
+        assert_eq!(
+            generator.render_page("post", page, &context).unwrap(),
+            "<h1>Hello</h1>\n"
+        );
+
+        let mut generator = generator_with(templates, "strict_templates = true");
+        generator.load_templates().unwrap();
+        let error = generator
+            .render_page("post", page, &context)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Template render error in 'post' at line 2, column 1 while building 'posts/hello.html': \
+             missing variable 'post.tittle' (available keys of 'post': slug, title)"
+        ));
+
+        let error = generator
+            .render_page("tag", Path::new("dist/tags/rust.html"), &context)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("while building 'tags/rust.html'"));
+        assert!(error.contains("use '@root.site.title' inside blocks"));
+    }
+
+    #[test]
+    fn test_duplicate_template_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let templates = temp_dir.path();
+        fs::write(templates.join("post.html"), "html").unwrap();
+        fs::write(templates.join("post.hbs"), "hbs").unwrap();
+
+        let error = generator(templates).load_templates().unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("Template 'post' is defined twice")
+        );
+    }
+
+    #[test]
+    fn test_theme_layers() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_templates = temp_dir.path().join("themes/paper/templates");
+        let project_templates = temp_dir.path().join("templates");
+        fs::create_dir_all(&theme_templates).unwrap();
+        fs::create_dir_all(&project_templates).unwrap();
+        fs::write(theme_templates.join("post.html"), "theme post").unwrap();
+        fs::write(theme_templates.join("index.html"), "theme index").unwrap();
+        fs::write(project_templates.join("index.hbs"), "project index").unwrap();
+
+        let mut generator = generator_with(
+            &project_templates,
+            &format!(
+                "theme = \"paper\"\nthemes_dir = \"{}\"",
+                temp_dir.path().join("themes").display()
+            ),
+        );
+        generator.load_templates().unwrap();
+
+        assert_eq!(generator.render("post", &json!({})).unwrap(), "theme post");
+        assert_eq!(
+            generator.render("index", &json!({})).unwrap(),
+            "project index"
+        );
+        assert!(generator.has_template("archive"));
+        assert!(generator.has_template("shortcodes/youtube"));
+    }
+
+    #[test]
+    fn test_missing_theme() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let error = generator_with(temp_dir.path(), "theme = \"absent\"")
+            .load_templates()
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Theme 'absent' not found"));
+    }
 
-            paragraphs.join("\n\n")
+    #[test]
+    fn test_translated_strings() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_i18n = temp_dir.path().join("themes/paper/i18n");
+        let project_i18n = temp_dir.path().join("i18n");
+        fs::create_dir_all(&theme_i18n).unwrap();
+        fs::create_dir_all(&project_i18n).unwrap();
+        fs::write(
+            theme_i18n.join("ru.toml"),
+            "[tag]\ntitle = \"Метка {name}\"\n",
+        )
+        .unwrap();
+        fs::write(project_i18n.join("ru.toml"), "[nav]\nhome = \"Домой\"\n").unwrap();
+        fs::write(
+            temp_dir.path().join("post.html"),
+            "{{t \"nav.home\"}}, {{t \"posts.count\" count=5}}",
+        )
+        .unwrap();
+
+        let mut generator = generator_with(
+            temp_dir.path(),
+            &format!(
+                "theme = \"paper\"\nthemes_dir = \"{}\"\ni18n_dir = \"{}\"",
+                temp_dir.path().join("themes").display(),
+                project_i18n.display()
+            ),
+        );
+        generator.load_templates().unwrap();
+
+        assert_eq!(generator.t("tag.title", &[("name", "rust")]), "Метка rust");
+        assert_eq!(generator.t("archive.title", &[]), "Архив");
+        assert_eq!(
+            generator.render("post", &json!({})).unwrap(),
+            "Домой, 5 постов"
+        );
+    }
+
+    /// Config of a site in `root` with every directory inside it.
+    fn site_config(root: &Path, extra: &str) -> Config {
+        let dir = |name: &str| root.join(name).display().to_string();
+        toml::from_str(&format!(
+            r#"
+[site]
+title = "Блог"
+description = "Test"
+author = "Tester"
+base_url = "https://example.com"
+language = "ru"
+
+[build]
+output_dir = "{}"
+content_dir = "{}"
+static_dir = "{}"
+templates_dir = "{}"
+data_dir = "{}"
+i18n_dir = "{}"
+
+{extra}
+"#,
+            dir("dist"),
+            dir("content"),
+            dir("static"),
+            dir("templates"),
+            dir("data"),
+            dir("i18n"),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_post_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content/talks/keynotes")).unwrap();
+        fs::create_dir_all(root.join("templates")).unwrap();
+        let post = |path: &str, front_matter: &str| {
+            fs::write(
+                root.join("content").join(path),
+                format!("---\ntitle: {path}\ndate: 2024-01-01\n{front_matter}---\n\nText\n"),
+            )
+            .unwrap();
         };
+        post("plain.md", "");
+        post("wide.md", "template: wide.html\n");
+        post("gallery.md", "layout: gallery\n");
+        post("missing.md", "template: nope\n");
+        post("talks/meetup.md", "");
+        post("talks/keynotes/opening.md", "");
+        post("talks/keynotes/custom.md", "template: wide\n");
+        for name in [
+            "post.html",
+            "wide.hbs",
+            "gallery.html",
+            "talk.html",
+            "keynote.html",
+        ] {
+            fs::write(root.join("templates").join(name), "").unwrap();
+        }
 
-        // Конвертируем Markdown в HTML
-        let parser = Parser::new(&excerpt_content);
-        let mut html_output = String::new();
-        html::push_html(&mut html_output, parser);
+        let config = site_config(
+            root,
+            "[layouts]\ntalks = \"talk\"\n\"talks/keynotes\" = \"keynote.html\"",
+        );
+        let mut generator = SiteGenerator::new(config).unwrap();
+        generator.load().unwrap();
+        let template = |generator: &SiteGenerator, slug: &str| {
+            let post = generator.posts().find(|post| post.slug == slug).unwrap();
+            generator.resolve_post_template(post)
+        };
+
+        assert_eq!(template(&generator, "plain").as_deref(), Some("post"));
+        assert_eq!(template(&generator, "wide").as_deref(), Some("wide"));
+        assert_eq!(template(&generator, "gallery").as_deref(), Some("gallery"));
+        assert_eq!(template(&generator, "meetup").as_deref(), Some("talk"));
+        // Ближайшая директория важнее родительской, front matter важнее обеих
+        assert_eq!(template(&generator, "opening").as_deref(), Some("keynote"));
+        assert_eq!(template(&generator, "custom").as_deref(), Some("wide"));
+        // Неизвестный шаблон заменяется на `post`
+        assert_eq!(template(&generator, "missing").as_deref(), Some("post"));
+
+        generator.handlebars.unregister_template("post");
+        assert_eq!(template(&generator, "missing"), None);
+        assert_eq!(template(&generator, "wide").as_deref(), Some("wide"));
+    }
+
+    #[test]
+    fn test_series_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content")).unwrap();
+        let post = |slug: &str, front_matter: &str| {
+            fs::write(
+                root.join("content").join(format!("{slug}.md")),
+                format!("---\ntitle: {slug}\n{front_matter}\n---\n\nText\n"),
+            )
+            .unwrap();
+        };
+        // Порядок задает series_order, а не дата; части без него идут в конце
+        post("intro", "date: 2024-03-01\nseries: Rust\nseries_order: 1");
+        post("traits", "date: 2024-01-01\nseries: Rust\nseries_order: 2");
+        post("extra", "date: 2024-02-01\nseries: Rust");
+        post("bonus", "date: 2024-04-01\nseries: Rust");
+        post("alone", "date: 2024-01-01");
+
+        let mut generator = SiteGenerator::new(site_config(root, "")).unwrap();
+        generator.load().unwrap();
+
+        let context = generator.page_context("post", Some("traits")).unwrap();
+        let series = &context["series"];
+        assert_eq!(series["name"], "Rust");
+        assert_eq!(series["url"], "/series/rust/");
+        assert_eq!(series["total"], 4);
+        assert_eq!(series["current"], 2);
+        let parts: Vec<(&Value, &Value, &Value)> = series["parts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|part| (&part["title"], &part["position"], &part["is_current"]))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                (&json!("intro"), &json!(1), &json!(false)),
+                (&json!("traits"), &json!(2), &json!(true)),
+                (&json!("extra"), &json!(3), &json!(false)),
+                (&json!("bonus"), &json!(4), &json!(false)),
+            ]
+        );
+        // Соседние части - предыдущая и следующая относительно текущей
+        assert_eq!(series["parts"][0]["url"], "/posts/intro");
+        assert_eq!(series["parts"][2]["url"], "/posts/extra");
+
+        let context = generator.page_context("post", Some("bonus")).unwrap();
+        assert_eq!(context["series"]["current"], 4);
+        assert!(generator.page_context("post", Some("alone")).unwrap()["series"].is_null());
+
+        let context = generator.page_context("series", Some("rust")).unwrap();
+        assert_eq!(context["series"]["current"], Value::Null);
+        assert_eq!(context["posts"][0]["title"], "intro");
+        assert_eq!(context["posts"][3]["title"], "bonus");
+        let context = generator.page_context("series_list", None).unwrap();
+        assert_eq!(context["all_series"][0]["total"], 4);
+    }
+
+    #[test]
+    fn test_author_pages_and_feeds() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content")).unwrap();
+        fs::create_dir_all(root.join("templates")).unwrap();
+        let post = |slug: &str, front_matter: &str| {
+            fs::write(
+                root.join("content").join(format!("{slug}.md")),
+                format!("---\ntitle: {slug}\n{front_matter}\n---\n\nText\n"),
+            )
+            .unwrap();
+        };
+        post("solo", "date: 2024-01-01\nauthor: ivan");
+        post("duo", "date: 2024-02-01\nauthors: [ivan, Guest Writer]");
+        post("default", "date: 2024-03-01");
+        let template = |name: &str, content: &str| {
+            fs::write(root.join("templates").join(name), content).unwrap();
+        };
+        template(
+            "post.html",
+            "{{post.author}}|{{post.author_info.id}}|{{#each post.authors}}{{this.url}} {{/each}}",
+        );
+        template(
+            "author.html",
+            "{{author.id}}|{{author.slug}}|{{author.name}}|{{#each posts}}{{this.title}} {{/each}}",
+        );
+        template(
+            "authors.html",
+            "{{#each all_authors}}{{this.id}}={{this.count}} {{/each}}",
+        );
+
+        let config = site_config(
+            root,
+            "[authors.ivan]\nname = \"Ivan Ivanov\"\nbio = \"Rust developer\"\n\
+             [authors.idle]\nname = \"Idle\"",
+        );
+        SiteGenerator::new(config).unwrap().build().unwrap();
+
+        let read = |path: &str| fs::read_to_string(root.join("dist").join(path)).unwrap();
+        // `post.author` остается строкой с именами, профили - в `authors`
+        assert_eq!(
+            read("posts/duo.html"),
+            "Ivan Ivanov, Guest Writer|ivan|/authors/ivan/ /authors/guest-writer/ "
+        );
+        assert_eq!(
+            read("posts/default.html"),
+            "Tester|Tester|/authors/tester/ "
+        );
+        assert_eq!(
+            read("authors/index.html"),
+            "Guest Writer=1 Tester=1 idle=0 ivan=2 "
+        );
+        assert_eq!(
+            read("authors/ivan/index.html"),
+            "ivan|ivan|Ivan Ivanov|duo solo "
+        );
+        assert_eq!(
+            read("authors/guest-writer/index.html"),
+            "Guest Writer|guest-writer|Guest Writer|duo "
+        );
+        assert_eq!(read("authors/idle/index.html"), "idle|idle|Idle|");
+
+        let feed = read("authors/ivan/feed.xml");
+        assert!(feed.contains("<title>Блог - Ivan Ivanov</title>"));
+        assert!(feed.contains("<description>Rust developer</description>"));
+        assert!(feed.contains("https://example.com/authors/ivan/"));
+        assert!(feed.contains("<title>duo</title>") && feed.contains("<title>solo</title>"));
+        assert!(!feed.contains("<title>default</title>"));
+    }
 
-        html_output
+    #[test]
+    fn test_page_bundle_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content/trip")).unwrap();
+        fs::create_dir_all(root.join("templates")).unwrap();
+        fs::write(
+            root.join("content/trip/index.md"),
+            "---\ntitle: Trip\ndate: 2024-01-01\n---\n\n![Map](map.png)\n",
+        )
+        .unwrap();
+        fs::write(root.join("content/trip/map.png"), b"png").unwrap();
+        fs::write(
+            root.join("content/note.md"),
+            "---\ntitle: Note\ndate: 2024-01-02\n---\n\nText\n",
+        )
+        .unwrap();
+        fs::write(root.join("templates/post.html"), "{{{post.content}}}").unwrap();
+
+        let mut config = site_config(root, "");
+        config.site.base_url = "https://example.com/blog/".to_string();
+        SiteGenerator::new(config).unwrap().build().unwrap();
+
+        // Страница бандла лежит в его директории, рядом с ресурсами
+        let dist = root.join("dist/posts");
+        assert!(!dist.join("trip.html").exists());
+        assert!(dist.join("trip/map.png").is_file());
+        let html = fs::read_to_string(dist.join("trip/index.html")).unwrap();
+        assert!(html.contains(r#"src="/blog/posts/trip/map.png""#));
+        assert!(dist.join("note.html").is_file());
+    }
+
+    #[test]
+    fn test_page_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content")).unwrap();
+        fs::create_dir_all(root.join("data")).unwrap();
+        fs::write(root.join("data/menu.json"), r#"["home"]"#).unwrap();
+        fs::write(
+            root.join("content/hello.md"),
+            "---\ntitle: Hello\ndate: 2024-01-01\ntags: [Rust Lang]\n---\n\nText\n",
+        )
+        .unwrap();
+
+        let mut generator = SiteGenerator::new(site_config(root, "")).unwrap();
+        generator.load().unwrap();
+
+        let context = generator.page_context("post", Some("hello")).unwrap();
+        assert_eq!(context["post"]["title"], "Hello");
+        assert_eq!(context["data"]["menu"], json!(["home"]));
+
+        // Тег ищется и по имени, и по слагу
+        let context = generator.page_context("tag", Some("rust-lang")).unwrap();
+        assert_eq!(context["tag"]["name"], "Rust Lang");
+        assert_eq!(context["posts"][0]["url"], "/posts/hello");
+        assert_eq!(
+            generator.page_context("tag", Some("Rust Lang")).unwrap(),
+            context
+        );
+
+        let error = generator.page_context("tag", None).unwrap_err();
+        assert!(error.to_string().contains("Available: Rust Lang"));
+        let error = generator.page_context("post", Some("nope")).unwrap_err();
+        assert!(error.to_string().contains("No post page named 'nope'"));
+        let error = generator.page_context("page", None).unwrap_err();
+        assert!(error.to_string().contains("Unknown page type 'page'"));
+        assert!(generator.set_language("en").is_err());
+
+        // Примеры не зависят от содержимого сайта, но берут его данные
+        let samples = generator.sample_contexts().unwrap();
+        let types: Vec<&str> = samples.iter().map(|(page_type, _)| *page_type).collect();
+        assert_eq!(types, PAGE_TYPES);
+        let posts = &samples[0].1;
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0]["series"]["name"], "Example series");
+        assert_eq!(posts[0]["post"]["image"], "/static/example.png");
+        assert!(posts[1]["series"].is_null() && posts[1]["post"]["image"].is_null());
+        assert_eq!(posts[1]["data"]["menu"], json!(["home"]));
+        let authors: Vec<&Value> = samples[10].1.iter().map(|c| &c["author"]["id"]).collect();
+        assert_eq!(authors, ["Guest Author", "Tester", EXAMPLE_AUTHOR]);
+        assert!(!root.join("dist").exists());
+    }
+
+    #[test]
+    fn test_seo_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content/hello")).unwrap();
+        fs::write(
+            root.join("content/hello/index.md"),
+            "---\ntitle: Hello\ndate: 2024-01-01\nupdated: 2024-02-01\nimage: cover.png\n\
+             tags: [rust]\ndescription: About </script>\n---\n\nText\n",
+        )
+        .unwrap();
+        fs::write(root.join("content/hello/cover.png"), b"png").unwrap();
+
+        let config = site_config(
+            root,
+            "[site.social]\ntwitter = \"https://twitter.com/blog/\"\n[features]\nseo_optimized = true",
+        );
+        let mut generator = SiteGenerator::new(config).unwrap();
+        generator.load().unwrap();
+
+        let context = generator.page_context("post", Some("hello")).unwrap();
+        assert_eq!(context["post"]["extra"]["image"], "cover.png");
+        let seo = &context["seo"];
+        assert_eq!(seo["canonical"], "https://example.com/posts/hello");
+        assert_eq!(seo["image"], "https://example.com/posts/hello/cover.png");
+        assert_eq!(seo["open_graph"]["type"], "article");
+        assert_eq!(seo["open_graph"]["article"]["modified_time"], "2024-02-01");
+        assert_eq!(seo["twitter"]["card"], "summary_large_image");
+        assert_eq!(seo["twitter"]["site"], "@blog");
+        let types: Vec<&Value> = seo["json_ld"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|ld| &ld["@type"])
+            .collect();
+        assert_eq!(types, ["WebSite", "BlogPosting", "BreadcrumbList"]);
+        assert_eq!(seo["json_ld"][1]["keywords"], json!(["rust"]));
+
+        let seo = &generator.page_context("tag", Some("rust")).unwrap()["seo"];
+        let crumbs: Vec<&Value> = seo["json_ld"][1]["itemListElement"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| &item["item"])
+            .collect();
+        assert_eq!(
+            crumbs,
+            [
+                "https://example.com/",
+                "https://example.com/tags",
+                "https://example.com/tags/rust"
+            ]
+        );
+        assert_eq!(seo["open_graph"]["type"], "website");
+        assert_eq!(seo["twitter"]["card"], "summary");
+
+        // Главная: имя сайта и без хлебных крошек
+        let seo = &generator.page_context("index", None).unwrap()["seo"];
+        assert_eq!(seo["title"], "Блог");
+        assert_eq!(seo["json_ld"].as_array().unwrap().len(), 1);
+        assert!(
+            generator
+                .page_context("rss", None)
+                .unwrap()
+                .get("seo")
+                .is_none()
+        );
+
+        // Базовая тема выводит метаданные, а JSON-LD не может закрыть <script>
+        generator.load_templates().unwrap();
+        let context = generator.create_post_data(
+            generator.posts().next().unwrap(),
+            &generator.collect_series(),
+            &generator.collect_translations(),
+        );
+        let html = generator.render("post", &context).unwrap();
+        assert!(
+            html.contains(r#"<link rel="canonical" href="https://example.com/posts/hello" />"#)
+        );
+        assert!(html.contains(r#""description":"About <\/script>""#));
+
+        // Без `seo_optimized` метаданные не собираются
+        let mut generator = SiteGenerator::new(site_config(root, "")).unwrap();
+        generator.load().unwrap();
+        for (page_type, name) in [("post", Some("hello")), ("index", None)] {
+            let context = generator.page_context(page_type, name).unwrap();
+            assert!(context.get("seo").is_none(), "{page_type}");
+        }
+    }
+
+    #[test]
+    fn test_twitter_handle() {
+        assert_eq!(
+            twitter_handle("https://x.com/blog").as_deref(),
+            Some("@blog")
+        );
+        assert_eq!(twitter_handle("@blog").as_deref(), Some("@blog"));
+        assert_eq!(twitter_handle("https://twitter.com/"), None);
+    }
+
+    #[test]
+    fn test_anchor_label_follows_post_language() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content/en")).unwrap();
+        for path in ["hello.md", "en/hello.md"] {
+            fs::write(
+                root.join("content").join(path),
+                "---\ntitle: Hello\ndate: 2024-01-01\n---\n\n## Intro\n",
+            )
+            .unwrap();
+        }
+
+        let config = site_config(
+            root,
+            "[content]\nadd_anchor_links = true\n[languages.en]\nname = \"English\"",
+        );
+        let mut generator = SiteGenerator::new(config).unwrap();
+        generator.load().unwrap();
+
+        let label = |url: &str| {
+            let post = generator.posts.iter().find(|post| post.url == url).unwrap();
+            let (_, rest) = post.html_content.split_once("aria-label=\"").unwrap();
+            rest.split('"').next().unwrap().to_string()
+        };
+        assert_eq!(label("/posts/hello"), "Ссылка на этот раздел");
+        assert_eq!(label("/en/posts/hello"), "Link to this section");
+    }
+
+    #[test]
+    fn test_multilingual_site() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content/en")).unwrap();
+        fs::create_dir_all(root.join("templates")).unwrap();
+        let post = |path: &str, front_matter: &str| {
+            fs::write(
+                root.join("content").join(path),
+                format!("---\n{front_matter}\n---\n\nText\n"),
+            )
+            .unwrap();
+        };
+        post("hello.md", "title: Привет\ndate: 2024-01-01");
+        post("en/hello.md", "title: Hello\ndate: 2024-01-01");
+        post("note.md", "title: Note\nlang: en\ndate: 2024-02-01");
+        post("draft.md", "title: Deutsch\nlang: de\ndate: 2024-02-01");
+        fs::write(
+            root.join("templates/post.html"),
+            "{{post.lang}}|{{#each post.translations}}{{this.name}}={{this.url}}{{/each}}|\
+             {{#each post.hreflang}}{{this.lang}} {{/each}}",
+        )
+        .unwrap();
+        fs::write(
+            root.join("templates/index.html"),
+            "{{site.title}}|{{t \"nav.home\"}}|{{#each posts}}{{this.url}} {{/each}}",
+        )
+        .unwrap();
+
+        let config = site_config(root, "[languages.en]\nname = \"English\"\ntitle = \"Blog\"");
+        SiteGenerator::new(config).unwrap().build().unwrap();
+
+        let read = |path: &str| fs::read_to_string(root.join("dist").join(path)).unwrap();
+        assert_eq!(
+            read("posts/hello.html"),
+            "ru|English=/en/posts/hello|ru en x-default "
+        );
+        assert_eq!(
+            read("en/posts/hello.html"),
+            "en|ru=/posts/hello|en ru x-default "
+        );
+        assert_eq!(read("en/posts/note.html"), "en||");
+        // Язык, которого нет в настройках, остается основным
+        assert_eq!(read("posts/draft.html"), "ru||");
+        assert_eq!(
+            read("index.html"),
+            "Блог|Главная|/posts/draft /posts/hello "
+        );
+        assert_eq!(
+            read("en/index.html"),
+            "Blog|Home|/en/posts/note /en/posts/hello "
+        );
+        assert!(root.join("dist/en/feed.xml").exists());
+        assert!(root.join("dist/en/tags/index.html").exists());
+    }
+
+    #[test]
+    fn test_multilingual_basic_theme_tag_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("content/en")).unwrap();
+        let post = "---\ntitle: Hello\ndate: 2024-01-01\ntags: [rust]\n---\n\nText\n";
+        fs::write(root.join("content/hello.md"), post).unwrap();
+        fs::write(root.join("content/en/hello.md"), post).unwrap();
+
+        // Без своих шаблонов сайт собирается базовой темой, в строгом режиме
+        let config = site_config(
+            root,
+            "strict_templates = true\n[languages.en]\nname = \"English\"",
+        );
+        SiteGenerator::new(config).unwrap().build().unwrap();
+
+        let read = |path: &str| fs::read_to_string(root.join("dist").join(path)).unwrap();
+        for page in ["en/posts/hello.html", "en/index.html"] {
+            assert!(
+                read(page).contains(r#"href="/en/tags/rust""#),
+                "{page} has no /en/ tag link"
+            );
+        }
+        assert!(read("posts/hello.html").contains(r#"href="/tags/rust""#));
+        assert!(!read("posts/hello.html").contains("/en/tags/rust"));
     }
 }
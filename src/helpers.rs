@@ -0,0 +1,456 @@
+//! Built-in Handlebars helpers available to every template.
+//!
+//! | Helper | Example |
+//! |--------|---------|
+//! | `date` | `{{date post.date_raw "%d.%m.%Y"}}` |
+//! | `slugify` | `{{slugify "Hello World"}}` |
+//! | `truncate` | `{{truncate post.summary 80}}` |
+//! | `url_for` | `{{url_for "/tags/rust"}}` |
+//! | `abs_url` | `{{abs_url post.url}}` |
+//! | `asset` | `{{{asset "css/main.css"}}}` |
+//! | `json` | `{{{json post.tags}}}` |
+//! | `markdown` | `{{{markdown author.bio}}}` |
+//! | `reading_time` | `{{reading_time post.content}}` |
+//! | `pluralize` | `{{pluralize count "пост" "поста" "постов"}}` |
+//! | `t` | `{{t "tag.count" count=(len posts) name=tag.name}}` |
+//!
+//! Comparisons (`eq`, `ne`, `gt`, `gte`, `lt`, `lte`) and `and`, `or`,
+//! `not`, `len` are built into Handlebars. All helpers return values, so
+//! they compose in subexpressions: `{{#if (gt (len posts) 1)}}`.
+
+use crate::config::Config;
+use crate::i18n::{Plural, Translations};
+use crate::images::fnv1a;
+use crate::markdown::{self, RenderOptions};
+use crate::templates::{self, BasicTemplate};
+use chrono::{DateTime, NaiveDateTime};
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason,
+    ScopedJson,
+};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Registers all built-in helpers.
+pub fn register(handlebars: &mut Handlebars<'_>, config: &Config) {
+    let date_format = config
+        .date_format
+        .as_ref()
+        .and_then(|df| df.posts.clone())
+        .unwrap_or_else(|| "%B %d, %Y".to_string());
+    let base_url = config.site.base_url.trim_end_matches('/').to_string();
+    let base_path = base_path(&base_url);
+    let options = RenderOptions::from_config(config);
+    let reading_speed = config.get_reading_speed().max(1);
+
+    register_fn(handlebars, "date", move |args| {
+        let value = args.str(0)?;
+        let format = args.opt_str(1).unwrap_or(&date_format);
+        Ok(parse_date(value)
+            .map_or_else(|| value.to_string(), |date| date.format(format).to_string())
+            .into())
+    });
+
+    register_fn(handlebars, "slugify", |args| {
+        Ok(markdown::slugify(args.str(0)?).into())
+    });
+
+    register_fn(handlebars, "truncate", |args| {
+        let length = args.number(1)?;
+        Ok(markdown::truncate(args.str(0)?, length).into())
+    });
+
+    let path_prefix = base_path.clone();
+    register_fn(handlebars, "url_for", move |args| {
+        Ok(join_url(&path_prefix, args.str(0)?).into())
+    });
+
+    register_fn(handlebars, "abs_url", move |args| {
+        Ok(join_url(&base_url, args.str(0)?).into())
+    });
+
+    // Статика ищется в том же порядке, в каком копируется в сборку
+    let mut static_dirs = vec![PathBuf::from(&config.build.static_dir)];
+    static_dirs.extend(
+        config
+            .get_theme_dir()
+            .map(|dir| dir.join(templates::STATIC_DIR)),
+    );
+    register_asset(handlebars, base_path, static_dirs);
+
+    register_fn(handlebars, "json", |args| {
+        let value = args.value(0)?;
+        let pretty = args
+            .hash
+            .get("pretty")
+            .is_some_and(|p| p.as_bool() == Some(true));
+        let json = if pretty {
+            serde_json::to_string_pretty(value)
+        } else {
+            serde_json::to_string(value)
+        };
+        // `</` экранируется, чтобы JSON можно было вставить в <script>
+        json.map(|json| Value::from(json.replace("</", "<\\/")))
+            .map_err(|e| RenderErrorReason::Other(e.to_string()).into())
+    });
+
+    register_fn(handlebars, "markdown", move |args| {
+        let events = markdown::parser(args.str(0)?, &options)
+            .map(|event| markdown::render_math(event).unwrap_or_else(|error| error.fallback));
+        let html = markdown::render(events, &options).html;
+        // Короткий текст из одного абзаца выводится без обертки `<p>`
+        let inline = args
+            .hash
+            .get("inline")
+            .is_some_and(|i| i.as_bool() == Some(true));
+        Ok(if inline {
+            let trimmed = html.trim();
+            trimmed
+                .strip_prefix("<p>")
+                .and_then(|html| html.strip_suffix("</p>"))
+                .unwrap_or(trimmed)
+                .to_string()
+        } else {
+            html
+        }
+        .into())
+    });
+
+    register_fn(handlebars, "reading_time", move |args| {
+        let words = strip_tags(args.str(0)?).split_whitespace().count();
+        Ok((words / reading_speed).max(1).into())
+    });
+
+    register_fn(handlebars, "pluralize", |args| {
+        let count = args.number(0)?;
+        let form = match (args.opt_str(2), args.opt_str(3)) {
+            (Some(few), Some(many)) => match Plural::of("ru", count as u64) {
+                Plural::One => args.str(1)?,
+                Plural::Few => few,
+                _ => many,
+            },
+            (Some(other), None) if count != 1 => other,
+            _ => args.str(1)?,
+        };
+        Ok(form.into())
+    });
+}
+
+/// Registers `t`, which looks up a translated string by key.
+///
+/// Strings come from the language of the page (`site.language`), falling
+/// back to the first of `translations`. Hash arguments fill `{name}`
+/// placeholders; `count` also picks the plural form.
+pub fn register_translations(handlebars: &mut Handlebars<'_>, translations: Vec<Translations>) {
+    register_fn(handlebars, "t", move |args| {
+        let key = args.str(0)?;
+        let Some(translations) = translations
+            .iter()
+            .find(|t| args.language.as_deref() == Some(t.language()))
+            .or_else(|| translations.first())
+        else {
+            return Ok(key.into());
+        };
+
+        let count = args.hash.get("count").and_then(Value::as_u64);
+        let values: Vec<(&str, String)> = args
+            .hash
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(text) => text.clone(),
+                    Value::Null => String::new(),
+                    other => other.to_string(),
+                };
+                (name.as_str(), value)
+            })
+            .collect();
+        let values: Vec<(&str, &str)> = values
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+
+        Ok(translations.translate(key, count, &values).into())
+    });
+}
+
+/// `asset` links a static file, adding a content hash so browsers refetch
+/// it after changes. The file is looked up in `static_dirs`, then in the
+/// embedded basic theme.
+fn register_asset(handlebars: &mut Handlebars<'_>, base_path: String, static_dirs: Vec<PathBuf>) {
+    let fingerprints: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+
+    register_fn(handlebars, "asset", move |args| {
+        let path = args.str(0)?.trim_start_matches('/');
+        let url = join_url(&base_path, &format!("static/{path}"));
+
+        let fingerprint = fingerprints
+            .lock()
+            .map_err(|_| RenderErrorReason::Other("Asset cache is poisoned".to_string()))?
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                static_dirs
+                    .iter()
+                    .find_map(|dir| fs::read(dir.join(path)).ok())
+                    .or_else(|| {
+                        BasicTemplate::get(&format!("{}/{path}", templates::STATIC_DIR))
+                            .map(|file| file.data.into_owned())
+                    })
+                    .map(|data| format!("{:08x}", fnv1a(&[&data]) & 0xffff_ffff))
+                    .unwrap_or_default()
+            })
+            .clone();
+
+        Ok(if fingerprint.is_empty() {
+            url
+        } else {
+            format!("{url}?v={fingerprint}")
+        }
+        .into())
+    });
+}
+
+/// Parameters of a helper call, resolved to owned JSON values.
+struct Args {
+    name: &'static str,
+    params: Vec<Value>,
+    hash: Map<String, Value>,
+    /// Language of the page being rendered, `site.language` of the root
+    /// context
+    language: Option<String>,
+}
+
+impl Args {
+    fn value(&self, index: usize) -> Result<&Value, RenderError> {
+        self.params
+            .get(index)
+            .ok_or_else(|| RenderErrorReason::ParamNotFoundForIndex(self.name, index).into())
+    }
+
+    /// A string parameter; missing values (`null`) become an empty string.
+    fn str(&self, index: usize) -> Result<&str, RenderError> {
+        match self.value(index)? {
+            Value::String(s) => Ok(s),
+            Value::Null => Ok(""),
+            _ => Err(RenderErrorReason::InvalidParamType("string").into()),
+        }
+    }
+
+    fn opt_str(&self, index: usize) -> Option<&str> {
+        self.params.get(index).and_then(Value::as_str)
+    }
+
+    fn number(&self, index: usize) -> Result<usize, RenderError> {
+        let value = self.value(index)?;
+        value
+            .as_u64()
+            .and_then(|n| usize::try_from(n).ok())
+            .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+            .ok_or_else(|| RenderErrorReason::InvalidParamType("number").into())
+    }
+}
+
+/// A helper backed by a closure over resolved parameters.
+struct FnHelper<F> {
+    name: &'static str,
+    function: F,
+}
+
+impl<F> HelperDef for FnHelper<F>
+where
+    F: Fn(&Args) -> Result<Value, RenderError> + Send + Sync,
+{
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        helper: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        context: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let args = Args {
+            name: self.name,
+            params: helper.params().iter().map(|p| p.value().clone()).collect(),
+            hash: helper
+                .hash()
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), value.value().clone()))
+                .collect(),
+            language: context
+                .data()
+                .pointer("/site/language")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        };
+        (self.function)(&args).map(ScopedJson::Derived)
+    }
+}
+
+fn register_fn<F>(handlebars: &mut Handlebars<'_>, name: &'static str, function: F)
+where
+    F: Fn(&Args) -> Result<Value, RenderError> + Send + Sync + 'static,
+{
+    handlebars.register_helper(name, Box::new(FnHelper { name, function }));
+}
+
+fn parse_date(date: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|dt| dt.naive_local())
+        .or_else(|_| NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default())
+        })
+        .ok()
+}
+
+/// Path part of the base URL, e.g. `/blog` for `https://example.com/blog`.
+pub(crate) fn base_path(base_url: &str) -> String {
+    let without_scheme = base_url
+        .split_once("://")
+        .map_or(base_url, |(_, rest)| rest);
+    without_scheme
+        .find('/')
+        .map(|index| without_scheme[index..].trim_end_matches('/').to_string())
+        .unwrap_or_default()
+}
+
+/// Joins a site path to `prefix`. Absolute URLs are returned unchanged.
+pub(crate) fn join_url(prefix: &str, path: &str) -> String {
+    if path.contains("://") || path.starts_with("//") {
+        return path.to_string();
+    }
+    format!("{prefix}/{}", path.trim_start_matches('/'))
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(template: &str, context: &Value) -> String {
+        let config: Config = toml::from_str(
+            r#"
+[site]
+title = "Test"
+description = "Test"
+author = "Tester"
+base_url = "https://example.com/blog/"
+
+[build]
+output_dir = "dist"
+content_dir = "content"
+static_dir = "static"
+templates_dir = "templates"
+"#,
+        )
+        .unwrap();
+        let mut handlebars = Handlebars::new();
+        register(&mut handlebars, &config);
+        handlebars.render_template(template, context).unwrap()
+    }
+
+    #[test]
+    fn test_date_and_slugify() {
+        let context = json!({"date": "2024-03-05", "title": "Привет, Rust мир"});
+
+        assert_eq!(
+            render(r#"{{date date "%d.%m.%Y"}} {{date date}}"#, &context),
+            "05.03.2024 March 05, 2024"
+        );
+        assert_eq!(render("{{slugify title}}", &context), "привет-rust-мир");
+    }
+
+    #[test]
+    fn test_urls() {
+        let context = json!({"url": "/posts/hello"});
+
+        assert_eq!(
+            render(
+                "{{url_for url}} {{abs_url url}} {{asset \"css/none.css\"}}",
+                &context
+            ),
+            "/blog/posts/hello https://example.com/blog/posts/hello /blog/static/css/none.css"
+        );
+        // Файла нет в проекте, но он есть во встроенной теме
+        assert!(
+            render("{{{asset \"css/main.css\"}}}", &context)
+                .starts_with("/blog/static/css/main.css?v=")
+        );
+    }
+
+    #[test]
+    fn test_truncate_json_and_markdown() {
+        let context = json!({"text": "one two three four", "tags": ["a", "b"], "bio": "*Hi*"});
+
+        assert_eq!(render("{{truncate text 10}}", &context), "one two…");
+        assert_eq!(render("{{{json tags}}}", &context), r#"["a","b"]"#);
+        assert_eq!(
+            render("{{{json this}}}", &json!("</script>")),
+            r#""<\/script>""#
+        );
+        assert_eq!(
+            render("{{{markdown bio}}}", &context),
+            "<p><em>Hi</em></p>\n"
+        );
+        assert_eq!(
+            render("{{{markdown bio inline=true}}}", &context),
+            "<em>Hi</em>"
+        );
+    }
+
+    #[test]
+    fn test_pluralize_and_comparisons() {
+        let forms = r#"{{pluralize n "пост" "поста" "постов"}}"#;
+        let plural = |n: usize| render(forms, &json!({ "n": n }));
+
+        assert_eq!(plural(1), "пост");
+        assert_eq!(plural(3), "поста");
+        assert_eq!(plural(11), "постов");
+        assert_eq!(plural(22), "поста");
+        assert_eq!(plural(25), "постов");
+        assert_eq!(
+            render(r#"{{pluralize 2 "post" "posts"}}"#, &json!({})),
+            "posts"
+        );
+        assert_eq!(
+            render(
+                "{{#if (gt (len items) 1)}}many{{/if}} {{#if (eq a \"x\")}}x{{/if}}",
+                &json!({"items": [1, 2], "a": "x"})
+            ),
+            "many x"
+        );
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let words = "word ".repeat(450);
+
+        assert_eq!(
+            render(
+                "{{reading_time text}}",
+                &json!({ "text": format!("<p>{words}</p>") })
+            ),
+            "2"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Translated strings of themes and generated pages.
+//!
+//! Strings are read from `i18n/<lang>.toml` files of the embedded basic
+//! theme, the selected theme and the project, later ones overriding
+//! earlier ones, like templates. English strings are loaded first, so a key
+//! missing from the site language falls back to English, and then to the
+//! key itself.
+//!
+//! Keys are dotted paths into the file (`tag.title`). A table with an
+//! `other` key holds plural forms (`one`, `few`, `many`, `other`) picked by
+//! the plural rules of the language. `{name}` placeholders are replaced
+//! with arguments.
+
+use crate::config::Config;
+use crate::error::{Result, ZahuyachError};
+use crate::templates::{self, BasicTemplate};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Language whose strings back up missing translations.
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// CLDR plural category of a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plural {
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+impl Plural {
+    /// Returns the category for `count` in `language` (`ru`, `en-US`, …).
+    #[must_use]
+    pub fn of(language: &str, count: u64) -> Self {
+        let language = language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let (last, last_two) = (count % 10, count % 100);
+        let few = (2..=4).contains(&last) && !(12..=14).contains(&last_two);
+
+        match language.as_str() {
+            // 1, 21 пост; 2–4, 22 поста; 5–20, 11–14 постов
+            "ru" | "uk" | "be" => match last {
+                1 if last_two != 11 => Self::One,
+                _ if few => Self::Few,
+                _ => Self::Many,
+            },
+            "pl" => match count {
+                1 => Self::One,
+                _ if few => Self::Few,
+                _ => Self::Many,
+            },
+            "cs" | "sk" => match count {
+                1 => Self::One,
+                2..=4 => Self::Few,
+                _ => Self::Other,
+            },
+            "fr" | "pt" => match count {
+                0 | 1 => Self::One,
+                _ => Self::Other,
+            },
+            "ja" | "zh" | "ko" | "vi" | "th" | "id" => Self::Other,
+            _ if count == 1 => Self::One,
+            _ => Self::Other,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::One => "one",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Message {
+    Text(String),
+    Plural(BTreeMap<String, String>),
+}
+
+/// Translated strings of one language.
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    language: String,
+    messages: HashMap<String, Message>,
+}
+
+impl Translations {
+    /// Loads strings for `language` from the basic theme, the selected
+    /// theme and the project `i18n_dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a translation file can't be read or parsed.
+    pub fn load(config: &Config, language: &str) -> Result<Self> {
+        let mut dirs: Vec<PathBuf> = config
+            .get_theme_dir()
+            .map(|dir| dir.join(templates::I18N_DIR))
+            .into_iter()
+            .collect();
+        dirs.push(PathBuf::from(config.get_i18n_dir()));
+
+        Self::from_dirs(language, &dirs)
+    }
+
+    /// Loads strings for `language` from the embedded basic theme and then
+    /// from `dirs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a translation file can't be read or parsed.
+    pub fn from_dirs(language: &str, dirs: &[PathBuf]) -> Result<Self> {
+        let mut translations = Self {
+            language: language.to_string(),
+            messages: HashMap::new(),
+        };
+
+        let mut languages = vec![FALLBACK_LANGUAGE];
+        if language != FALLBACK_LANGUAGE {
+            languages.push(language);
+        }
+
+        for lang in languages {
+            let file = format!("{lang}.toml");
+            let embedded = format!("{}/{file}", templates::I18N_DIR);
+            if let Some(data) = BasicTemplate::get(&embedded) {
+                translations.merge(&String::from_utf8_lossy(&data.data), Path::new(&embedded))?;
+            }
+
+            for path in dirs.iter().map(|dir| dir.join(&file)) {
+                if path.exists() {
+                    translations.merge(&fs::read_to_string(&path)?, &path)?;
+                }
+            }
+        }
+
+        Ok(translations)
+    }
+
+    fn merge(&mut self, source: &str, path: &Path) -> Result<()> {
+        let table: toml::Table = toml::from_str(source).map_err(|e| {
+            ZahuyachError::InvalidInput(format!(
+                "Invalid translation file '{}': {e}",
+                path.display()
+            ))
+        })?;
+        self.merge_table(&table, "");
+        Ok(())
+    }
+
+    fn merge_table(&mut self, table: &toml::Table, prefix: &str) {
+        for (key, value) in table {
+            let key = format!("{prefix}{key}");
+            match value {
+                toml::Value::Table(forms) if forms.contains_key("other") => {
+                    let forms = forms
+                        .iter()
+                        .filter_map(|(form, text)| Some((form.clone(), text.as_str()?.to_string())))
+                        .collect();
+                    self.messages.insert(key, Message::Plural(forms));
+                }
+                toml::Value::Table(nested) => self.merge_table(nested, &format!("{key}.")),
+                toml::Value::String(text) => {
+                    self.messages.insert(key, Message::Text(text.clone()));
+                }
+                other => {
+                    self.messages.insert(key, Message::Text(other.to_string()));
+                }
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    #[must_use]
+    pub fn contains(&self, key: &str) -> bool {
+        self.messages.contains_key(key)
+    }
+
+    /// Returns the string for `key` with placeholders replaced by `args`.
+    /// Plural forms are picked by `count`, which is also available as
+    /// `{count}`. Unknown keys are returned as is.
+    #[must_use]
+    pub fn translate(&self, key: &str, count: Option<u64>, args: &[(&str, &str)]) -> String {
+        let template = match self.messages.get(key) {
+            Some(Message::Text(text)) => text.as_str(),
+            Some(Message::Plural(forms)) => {
+                let category = count.map_or(Plural::Other, |n| Plural::of(&self.language, n));
+                forms
+                    .get(category.as_str())
+                    .or_else(|| forms.get(Plural::Other.as_str()))
+                    .map_or(key, String::as_str)
+            }
+            None => return key.to_string(),
+        };
+
+        let count = count.map(|n| n.to_string());
+        let mut args = args.to_vec();
+        if let Some(count) = &count {
+            args.push(("count", count));
+        }
+        interpolate(template, &args)
+    }
+}
+
+/// Replaces `{name}` placeholders; unknown ones are kept.
+fn interpolate(template: &str, args: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+            Some((*value, end))
+        });
+
+        if let Some((value, end)) = value {
+            result.push_str(value);
+            rest = &after[end + 1..];
+        } else {
+            result.push('{');
+            rest = after;
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plural_rules() {
+        let forms = |language: &str| {
+            [0, 1, 2, 5, 11, 21, 22, 112]
+                .map(|n| Plural::of(language, n).as_str())
+                .join(" ")
+        };
+
+        assert_eq!(forms("en"), "other one other other other other other other");
+        assert_eq!(forms("ru-RU"), "many one few many many one few many");
+        assert_eq!(forms("pl"), "many one few many many many few many");
+        assert_eq!(forms("fr"), "one one other other other other other other");
+        assert_eq!(
+            forms("ja"),
+            "other other other other other other other other"
+        );
+    }
+
+    #[test]
+    fn test_translate_with_fallbacks() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("ru.toml"),
+            "[tag]\ntitle = \"Метка: {name}\"\n[custom]\nfiles = { one = \"{count} файл\", few = \"{count} файла\", many = \"{count} файлов\", other = \"{count} файла\" }\n",
+        )
+        .unwrap();
+        let translations = Translations::from_dirs("ru", &[dir.path().to_path_buf()]).unwrap();
+
+        // Проект переопределяет встроенную тему
+        assert_eq!(
+            translations.translate("tag.title", None, &[("name", "rust")]),
+            "Метка: rust"
+        );
+        assert_eq!(translations.translate("nav.home", None, &[]), "Главная");
+        assert_eq!(
+            translations.translate("custom.files", Some(3), &[]),
+            "3 файла"
+        );
+        assert_eq!(
+            translations.translate("custom.files", Some(11), &[]),
+            "11 файлов"
+        );
+        assert_eq!(
+            translations.translate("missing.key", None, &[]),
+            "missing.key"
+        );
+
+        // Язык без перевода получает английские строки
+        let translations = Translations::from_dirs("de", &[]).unwrap();
+        assert_eq!(
+            translations.translate("posts.count", Some(1), &[]),
+            "1 post"
+        );
+        assert_eq!(
+            translations.translate("tag.count", Some(2), &[("name", "rust")]),
+            "2 posts tagged with \"rust\""
+        );
+    }
+
+    #[test]
+    fn test_interpolate_keeps_unknown_placeholders() {
+        let args = [("name", "x")];
+        assert_eq!(interpolate("{name} {other} {", &args), "x {other} {");
+    }
+
+    #[test]
+    fn test_invalid_translation_file() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("en.toml"), "title = ").unwrap();
+
+        let error = Translations::from_dirs("en", &[dir.path().to_path_buf()]).unwrap_err();
+        assert!(error.to_string().contains("Invalid translation file"));
+    }
+}
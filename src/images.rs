@@ -0,0 +1,829 @@
+//! Responsive images: resized variants, modern formats and `srcset` markup.
+//!
+//! Images referenced by posts are resized to the configured widths and
+//! optionally re-encoded as WebP/AVIF. Results are cached in `cache_dir`,
+//! keyed by the image contents and settings, so unchanged images are only
+//! copied on later builds.
+
+use crate::config::Config;
+use crate::error::{Result, ZahuyachError};
+use crate::markdown::escape_html;
+use image::DynamicImage;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Bumped when the output of the pipeline changes, invalidating the cache.
+const CACHE_VERSION: u32 = 3;
+const MANIFEST: &str = "manifest.json";
+
+/// Image formats the pipeline reads and writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Jpeg,
+    Png,
+    Webp,
+    Avif,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+            Self::Avif => "avif",
+        }
+    }
+
+    const fn mime_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+            Self::Avif => "image/avif",
+        }
+    }
+}
+
+/// Settings derived from the `[images]` config section.
+#[derive(Debug, Clone)]
+pub struct ImageSettings {
+    pub widths: Vec<u32>,
+    /// Formats generated in addition to the original one
+    pub formats: Vec<Format>,
+    pub quality: u8,
+    pub sizes: String,
+    pub cache_dir: PathBuf,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self {
+            widths: vec![480, 768, 1200],
+            formats: Vec::new(),
+            quality: 80,
+            sizes: "(max-width: 768px) 100vw, 768px".to_string(),
+            cache_dir: PathBuf::from(".zahuyach-cache/images"),
+        }
+    }
+}
+
+impl ImageSettings {
+    /// # Errors
+    ///
+    /// Returns an error for unknown output formats or a quality outside 1-100.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let defaults = Self::default();
+        let Some(images) = config.images.as_ref() else {
+            return Ok(defaults);
+        };
+
+        let formats: Vec<Format> = images
+            .formats
+            .iter()
+            .flatten()
+            .map(|name| match name.to_lowercase().as_str() {
+                "webp" => Ok(Format::Webp),
+                "avif" => Ok(Format::Avif),
+                _ => Err(ZahuyachError::InvalidInput(format!(
+                    "Unknown image format '{name}', expected 'webp' or 'avif'"
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Браузер берет первый поддерживаемый `<source>`: AVIF компактнее WebP
+        let mut formats = formats;
+        formats.sort_by_key(|format| *format != Format::Avif);
+        formats.dedup();
+
+        let quality = images.quality.unwrap_or(defaults.quality);
+        if !(1..=100).contains(&quality) {
+            return Err(ZahuyachError::InvalidInput(format!(
+                "Image quality must be between 1 and 100, got {quality}"
+            )));
+        }
+
+        let mut widths = images.widths.clone().unwrap_or(defaults.widths);
+        widths.retain(|&width| width > 0);
+        widths.sort_unstable();
+        widths.dedup();
+
+        Ok(Self {
+            widths,
+            formats,
+            quality,
+            sizes: images.sizes.clone().unwrap_or(defaults.sizes),
+            cache_dir: images
+                .cache_dir
+                .as_ref()
+                .map_or(defaults.cache_dir, PathBuf::from),
+        })
+    }
+
+    fn cache_key(&self, data: &[u8]) -> String {
+        let settings = format!(
+            "{CACHE_VERSION}:{:?}:{:?}:{}",
+            self.widths, self.formats, self.quality
+        );
+        format!("{:016x}", fnv1a(&[settings.as_bytes(), data]))
+    }
+}
+
+/// Generated variants of one source image, stored next to them in the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    width: u32,
+    height: u32,
+    variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Variant {
+    format: Format,
+    width: u32,
+    /// File name in the cache entry, e.g. `480w.webp`
+    file: String,
+}
+
+impl Variant {
+    /// Name of the variant next to `source`, e.g. `photo-png-480w.webp`.
+    ///
+    /// Cache entries are shared by identical images, so the name comes from
+    /// the source being processed. Its extension keeps `photo.png` and
+    /// `photo.jpg` from one directory from overwriting each other.
+    fn output_name(&self, source: &Path) -> String {
+        let stem = source
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("image");
+        let extension = source
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        format!("{stem}-{extension}-{}", self.file)
+    }
+}
+
+/// Resizes images and rewrites `<img>` tags to use the results.
+pub struct ImageProcessor {
+    settings: ImageSettings,
+    /// Results per source file and output directory; `None` for
+    /// unsupported formats
+    processed: HashMap<(PathBuf, PathBuf), Option<Manifest>>,
+    /// Images encoded during this build
+    pub generated: usize,
+    /// Images taken from the cache
+    pub cached: usize,
+}
+
+impl ImageProcessor {
+    #[must_use]
+    pub fn new(settings: ImageSettings) -> Self {
+        Self {
+            settings,
+            processed: HashMap::new(),
+            generated: 0,
+            cached: 0,
+        }
+    }
+
+    /// Rewrites `<img>` tags in `html` with `srcset`, `sizes`, intrinsic
+    /// `width`/`height` and `loading="lazy"`, wrapping them in `<picture>`
+    /// when extra formats are configured.
+    ///
+    /// `resolve` maps an image `src` to its source file and the directory
+    /// the variants are written to; images it returns `None` for, and
+    /// images that already have a `srcset`, are left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an image can't be read, decoded or encoded.
+    pub fn rewrite_html(
+        &mut self,
+        html: &str,
+        resolve: impl Fn(&str) -> Option<(PathBuf, PathBuf)>,
+    ) -> Result<String> {
+        let mut output = String::with_capacity(html.len());
+        let mut rest = html;
+
+        while let Some(start) = find_img(rest) {
+            output.push_str(&rest[..start]);
+            let Some(length) = tag_length(&rest[start..]) else {
+                rest = &rest[start..];
+                break;
+            };
+            let tag = &rest[start..start + length];
+
+            match self.rewrite_tag(tag, &resolve)? {
+                Some(rewritten) => output.push_str(&rewritten),
+                None => output.push_str(tag),
+            }
+            rest = &rest[start + length..];
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn rewrite_tag(
+        &mut self,
+        tag: &str,
+        resolve: &impl Fn(&str) -> Option<(PathBuf, PathBuf)>,
+    ) -> Result<Option<String>> {
+        let mut attributes = parse_attributes(tag);
+        if get(&attributes, "srcset").is_some() {
+            return Ok(None);
+        }
+        let Some(src) = get(&attributes, "src").map(str::to_string) else {
+            return Ok(None);
+        };
+        let Some((source, output_dir)) = resolve(&src) else {
+            return Ok(None);
+        };
+        let Some(manifest) = self.process(&source, &output_dir)? else {
+            return Ok(None);
+        };
+        let Some(original) = Format::from_path(&source) else {
+            return Ok(None);
+        };
+
+        // Варианты лежат рядом с оригиналом
+        let url = |file: &str| match src.rsplit_once('/') {
+            Some((dir, _)) => format!("{dir}/{file}"),
+            None => file.to_string(),
+        };
+        let srcset = |format: Format| {
+            let mut entries: Vec<String> = manifest
+                .variants
+                .iter()
+                .filter(|variant| variant.format == format)
+                .map(|variant| format!("{} {}w", url(&variant.output_name(&source)), variant.width))
+                .collect();
+            if format == original {
+                entries.push(format!("{src} {}w", manifest.width));
+            }
+            entries.join(", ")
+        };
+        let sizes = escape_html(&self.settings.sizes);
+
+        set(&mut attributes, "srcset", &srcset(original), true);
+        set(&mut attributes, "sizes", &sizes, false);
+        if get(&attributes, "width").is_none() && get(&attributes, "height").is_none() {
+            set(&mut attributes, "width", &manifest.width.to_string(), false);
+            set(
+                &mut attributes,
+                "height",
+                &manifest.height.to_string(),
+                false,
+            );
+        }
+        set(&mut attributes, "loading", "lazy", false);
+        set(&mut attributes, "decoding", "async", false);
+
+        let mut img = String::from("<img");
+        for (name, value) in &attributes {
+            match value {
+                Some(value) => {
+                    let _ = write!(img, r#" {name}="{value}""#);
+                }
+                None => {
+                    let _ = write!(img, " {name}");
+                }
+            }
+        }
+        img.push_str(" />");
+
+        // Формат оригинала уже описан в srcset самого `<img>`, а форматы без
+        // вариантов (все вышли тяжелее оригинала) не нужны
+        let extra_formats: Vec<Format> = self
+            .settings
+            .formats
+            .iter()
+            .copied()
+            .filter(|&format| format != original)
+            .filter(|&format| manifest.variants.iter().any(|v| v.format == format))
+            .collect();
+        if extra_formats.is_empty() {
+            return Ok(Some(img));
+        }
+
+        let mut picture = String::from("<picture>");
+        for format in extra_formats {
+            let _ = write!(
+                picture,
+                r#"<source type="{}" srcset="{}" sizes="{sizes}" />"#,
+                format.mime_type(),
+                srcset(format)
+            );
+        }
+        picture.push_str(&img);
+        picture.push_str("</picture>");
+        Ok(Some(picture))
+    }
+
+    /// Generates (or restores from the cache) the variants of `source` and
+    /// copies them to `output_dir`.
+    fn process(&mut self, source: &Path, output_dir: &Path) -> Result<Option<Manifest>> {
+        let key = (source.to_path_buf(), output_dir.to_path_buf());
+        if let Some(manifest) = self.processed.get(&key) {
+            return Ok(manifest.clone());
+        }
+        if Format::from_path(source).is_none() || !source.is_file() {
+            self.processed.insert(key, None);
+            return Ok(None);
+        }
+
+        let data = fs::read(source)?;
+        let entry = self.settings.cache_dir.join(self.settings.cache_key(&data));
+        let manifest_path = entry.join(MANIFEST);
+
+        // Запись без какого-либо из вариантов считается промахом кэша
+        let cached = fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Manifest>(&json).ok())
+            .filter(|manifest| {
+                manifest
+                    .variants
+                    .iter()
+                    .all(|variant| entry.join(&variant.file).is_file())
+            });
+        let manifest = if let Some(manifest) = cached {
+            self.cached += 1;
+            manifest
+        } else {
+            let manifest = self.generate(source, &data, &entry)?;
+            let json = serde_json::to_string_pretty(&manifest)
+                .map_err(|e| ZahuyachError::InvalidInput(e.to_string()))?;
+            fs::write(&manifest_path, json)?;
+            self.generated += 1;
+            manifest
+        };
+
+        fs::create_dir_all(output_dir)?;
+        for variant in &manifest.variants {
+            let cached = entry.join(&variant.file);
+            fs::copy(&cached, output_dir.join(variant.output_name(source))).map_err(|e| {
+                ZahuyachError::InvalidInput(format!(
+                    "Failed to copy '{}' for image '{}': {e}",
+                    cached.display(),
+                    source.display()
+                ))
+            })?;
+        }
+
+        self.processed.insert(key, Some(manifest.clone()));
+        Ok(Some(manifest))
+    }
+
+    fn generate(&self, source: &Path, data: &[u8], entry: &Path) -> Result<Manifest> {
+        let image = image::load_from_memory(data).map_err(|e| {
+            ZahuyachError::InvalidInput(format!(
+                "Failed to decode image '{}': {e}",
+                source.display()
+            ))
+        })?;
+        let (width, height) = (image.width(), image.height());
+        let original = Format::from_path(source).unwrap_or(Format::Png);
+
+        // Больше оригинала не увеличиваем: самый широкий вариант - исходный размер
+        let widths: Vec<u32> = self
+            .settings
+            .widths
+            .iter()
+            .copied()
+            .filter(|&w| w < width)
+            .chain([width])
+            .collect();
+
+        fs::create_dir_all(entry)?;
+        let mut variants = Vec::new();
+        // Размеры файлов в формате оригинала по ширине, для сравнения
+        let mut original_sizes = HashMap::from([(width, data.len() as u64)]);
+
+        let extra_formats = self
+            .settings
+            .formats
+            .iter()
+            .copied()
+            .filter(|&format| format != original);
+        for format in std::iter::once(original).chain(extra_formats) {
+            for &target in &widths {
+                // Исходный файл уже служит вариантом полной ширины
+                if format == original && target == width {
+                    continue;
+                }
+
+                let resized = if target == width {
+                    image.clone()
+                } else {
+                    let target_height = scaled_height(width, height, target);
+                    image.resize_exact(target, target_height, FilterType::Lanczos3)
+                };
+
+                let file = format!("{target}w.{}", format.extension());
+                let path = entry.join(&file);
+                encode(&resized, format, self.settings.quality, &path).map_err(|e| {
+                    ZahuyachError::InvalidInput(format!(
+                        "Failed to encode '{}' as {}: {e}",
+                        source.display(),
+                        format.extension()
+                    ))
+                })?;
+
+                // WebP кодируется без потерь и для фотографий бывает тяжелее
+                // оригинала: такой вариант браузер скачал бы вместо него
+                let size = fs::metadata(&path)?.len();
+                if format == original {
+                    original_sizes.insert(target, size);
+                } else if original_sizes
+                    .get(&target)
+                    .is_some_and(|&other| size >= other)
+                {
+                    fs::remove_file(&path)?;
+                    continue;
+                }
+                variants.push(Variant {
+                    format,
+                    width: target,
+                    file,
+                });
+            }
+        }
+
+        Ok(Manifest {
+            width,
+            height,
+            variants,
+        })
+    }
+}
+
+fn scaled_height(width: u32, height: u32, target: u32) -> u32 {
+    let scaled = u64::from(height) * u64::from(target) / u64::from(width);
+    u32::try_from(scaled).unwrap_or(u32::MAX).max(1)
+}
+
+fn encode(
+    image: &DynamicImage,
+    format: Format,
+    quality: u8,
+    path: &Path,
+) -> image::ImageResult<()> {
+    let writer = BufWriter::new(fs::File::create(path)?);
+    match format {
+        // JPEG не поддерживает прозрачность
+        Format::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(writer, quality)),
+        Format::Png => image.write_with_encoder(PngEncoder::new(writer)),
+        Format::Webp => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(writer)),
+        Format::Avif => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_with_encoder(AvifEncoder::new_with_speed_quality(writer, 8, quality)),
+    }
+}
+
+/// 64-bit FNV-1a: a stable hash for cache keys.
+pub(crate) fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn find_img(html: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(index) = html[offset..].find("<img") {
+        let start = offset + index;
+        let next = html[start + "<img".len()..].chars().next();
+        if next.is_some_and(|c| c.is_whitespace() || c == '/' || c == '>') {
+            return Some(start);
+        }
+        offset = start + "<img".len();
+    }
+    None
+}
+
+/// Length of the tag at the start of `html`, up to and including `>`.
+fn tag_length(html: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in html.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+type Attributes = Vec<(String, Option<String>)>;
+
+fn parse_attributes(tag: &str) -> Attributes {
+    let inner = tag
+        .trim_start_matches("<img")
+        .trim_end_matches('>')
+        .trim_end_matches('/');
+    let mut attributes = Vec::new();
+    let mut chars = inner.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == '/').is_some() {}
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            name.push(c);
+        }
+        if name.is_empty() {
+            break;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let value = chars.next_if_eq(&'=').map(|_| {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if let Some(quote) = chars.next_if(|c| *c == '"' || *c == '\'') {
+                chars.by_ref().take_while(|&c| c != quote).collect()
+            } else {
+                let mut value = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+                value
+            }
+        });
+        attributes.push((name.to_lowercase(), value));
+    }
+
+    attributes
+}
+
+fn get<'a>(attributes: &'a Attributes, name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(key, _)| key == name)
+        .and_then(|(_, value)| value.as_deref())
+}
+
+/// Sets an attribute, keeping an existing value unless `replace` is set.
+fn set(attributes: &mut Attributes, name: &str, value: &str, replace: bool) {
+    match attributes.iter_mut().find(|(key, _)| key == name) {
+        Some((_, existing)) if replace || existing.is_none() => *existing = Some(value.to_string()),
+        Some(_) => {}
+        None => attributes.push((name.to_string(), Some(value.to_string()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+    use tempfile::TempDir;
+
+    fn settings(cache_dir: &Path, formats: Vec<Format>) -> ImageSettings {
+        ImageSettings {
+            widths: vec![40, 80, 200],
+            formats,
+            quality: 80,
+            sizes: "100vw".to_string(),
+            cache_dir: cache_dir.to_path_buf(),
+        }
+    }
+
+    fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("pic.png");
+        RgbImage::from_fn(100, 50, |x, _| Rgb([u8::try_from(x).unwrap(), 0, 0]))
+            .save(&source)
+            .unwrap();
+        let output = temp_dir.path().join("public/posts/p");
+        (temp_dir, source, output)
+    }
+
+    #[test]
+    fn test_rewrite_img_with_srcset() {
+        let (temp_dir, source, output) = setup();
+        let mut processor = ImageProcessor::new(settings(&temp_dir.path().join("cache"), vec![]));
+
+        let html = processor
+            .rewrite_html(
+                r#"<p><img src="/posts/p/pic.png" alt="A &amp; B" /> <img src="https://example.com/x.png" alt="" /></p>"#,
+                |src| (src == "/posts/p/pic.png").then(|| (source.clone(), output.clone())),
+            )
+            .unwrap();
+
+        assert_eq!(
+            html,
+            r#"<p><img src="/posts/p/pic.png" alt="A &amp; B" srcset="/posts/p/pic-png-40w.png 40w, /posts/p/pic-png-80w.png 80w, /posts/p/pic.png 100w" sizes="100vw" width="100" height="50" loading="lazy" decoding="async" /> <img src="https://example.com/x.png" alt="" /></p>"#
+        );
+        assert!(output.join("pic-png-40w.png").is_file());
+        assert!(output.join("pic-png-80w.png").is_file());
+        assert_eq!(
+            image::open(output.join("pic-png-40w.png"))
+                .unwrap()
+                .height(),
+            20
+        );
+    }
+
+    #[test]
+    fn test_extra_formats_use_picture() {
+        let (temp_dir, source, output) = setup();
+        let mut processor =
+            ImageProcessor::new(settings(&temp_dir.path().join("cache"), vec![Format::Webp]));
+
+        let html = processor
+            .rewrite_html(r#"<img src="pic.png" alt="">"#, |_| {
+                Some((source.clone(), output.clone()))
+            })
+            .unwrap();
+
+        assert!(html.starts_with(
+            r#"<picture><source type="image/webp" srcset="pic-png-40w.webp 40w, pic-png-80w.webp 80w, pic-png-100w.webp 100w" sizes="100vw" /><img src="pic.png""#
+        ));
+        assert!(html.ends_with("</picture>"));
+        assert!(output.join("pic-png-100w.webp").is_file());
+    }
+
+    #[test]
+    fn test_cache_is_reused() {
+        let (temp_dir, source, output) = setup();
+        let cache = temp_dir.path().join("cache");
+        let html = r#"<img src="/posts/p/pic.png" />"#;
+        let resolve = |_: &str| Some((source.clone(), output.clone()));
+
+        let mut first = ImageProcessor::new(settings(&cache, vec![]));
+        let expected = first.rewrite_html(html, resolve).unwrap();
+        // Повторное использование в том же билде не пересчитывается
+        first.rewrite_html(html, resolve).unwrap();
+        assert_eq!((first.generated, first.cached), (1, 0));
+
+        fs::remove_dir_all(&output).unwrap();
+        let mut second = ImageProcessor::new(settings(&cache, vec![]));
+        assert_eq!(second.rewrite_html(html, resolve).unwrap(), expected);
+        assert_eq!((second.generated, second.cached), (0, 1));
+        assert!(output.join("pic-png-80w.png").is_file());
+    }
+
+    #[test]
+    fn test_missing_cached_variant_is_regenerated() {
+        let (temp_dir, source, output) = setup();
+        let cache = temp_dir.path().join("cache");
+        let resolve = |_: &str| Some((source.clone(), output.clone()));
+
+        ImageProcessor::new(settings(&cache, vec![]))
+            .rewrite_html(r#"<img src="pic.png">"#, resolve)
+            .unwrap();
+        let entry = fs::read_dir(&cache)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        fs::remove_file(entry.join("40w.png")).unwrap();
+        fs::remove_dir_all(&output).unwrap();
+
+        let mut processor = ImageProcessor::new(settings(&cache, vec![]));
+        processor
+            .rewrite_html(r#"<img src="pic.png">"#, resolve)
+            .unwrap();
+        assert_eq!((processor.generated, processor.cached), (1, 0));
+        assert!(entry.join("40w.png").is_file());
+        assert!(output.join("pic-png-40w.png").is_file());
+    }
+
+    #[test]
+    fn test_same_image_in_two_directories() {
+        let (temp_dir, source, output) = setup();
+        let other = temp_dir.path().join("public/en/posts/p");
+        let mut processor = ImageProcessor::new(settings(&temp_dir.path().join("cache"), vec![]));
+
+        for dir in [&output, &other] {
+            processor
+                .rewrite_html(r#"<img src="pic.png">"#, |_| {
+                    Some((source.clone(), dir.clone()))
+                })
+                .unwrap();
+            assert!(dir.join("pic-png-40w.png").is_file());
+        }
+        assert_eq!(processor.generated, 1);
+    }
+
+    #[test]
+    fn test_heavier_variants_are_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("photo.jpg");
+        let output = temp_dir.path().join("public");
+        // Шум похож на фотографию: без потерь он сжимается хуже JPEG
+        let mut seed: u32 = 1;
+        let mut noise = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            u8::try_from(seed >> 24).unwrap()
+        };
+        RgbImage::from_fn(200, 100, |_, _| Rgb([noise(), noise(), noise()]))
+            .save(&source)
+            .unwrap();
+        let mut processor =
+            ImageProcessor::new(settings(&temp_dir.path().join("cache"), vec![Format::Webp]));
+
+        let html = processor
+            .rewrite_html(r#"<img src="photo.jpg">"#, |_| {
+                Some((source.clone(), output.clone()))
+            })
+            .unwrap();
+
+        let size = |file: &str| fs::metadata(output.join(file)).map(|m| m.len());
+        for width in [40, 80, 200] {
+            let Ok(webp) = size(&format!("photo-jpg-{width}w.webp")) else {
+                continue;
+            };
+            let jpeg = if width == 200 {
+                fs::metadata(&source).unwrap().len()
+            } else {
+                size(&format!("photo-jpg-{width}w.jpg")).unwrap()
+            };
+            assert!(webp < jpeg, "{width}w: {webp} >= {jpeg}");
+        }
+        assert!(!output.join("photo-jpg-200w.webp").exists());
+        // `<source>` остается, только если какой-то WebP вышел легче
+        let has_webp = [40, 80]
+            .iter()
+            .any(|w| size(&format!("photo-jpg-{w}w.webp")).is_ok());
+        assert_eq!(html.contains(r#"<source type="image/webp""#), has_webp);
+    }
+
+    #[test]
+    fn test_variant_names_keep_source_extension() {
+        let (temp_dir, png, output) = setup();
+        let jpg = temp_dir.path().join("pic.jpg");
+        image::open(&png).unwrap().save(&jpg).unwrap();
+        let mut processor = ImageProcessor::new(settings(&temp_dir.path().join("cache"), vec![]));
+
+        // Копия с тем же содержимым берет варианты из кэша под своим именем
+        fs::copy(&png, temp_dir.path().join("copy.png")).unwrap();
+        let html = processor
+            .rewrite_html(
+                r#"<img src="pic.png"><img src="pic.jpg"><img src="copy.png">"#,
+                |src| Some((temp_dir.path().join(src), output.clone())),
+            )
+            .unwrap();
+
+        assert!(output.join("pic-png-40w.png").is_file());
+        assert!(output.join("pic-jpg-40w.jpg").is_file());
+        assert!(html.contains("copy-png-40w.png 40w"));
+        assert!(output.join("copy-png-40w.png").is_file());
+    }
+
+    #[test]
+    fn test_original_format_is_not_repeated() {
+        let (temp_dir, png, output) = setup();
+        let source = temp_dir.path().join("pic.webp");
+        image::open(&png).unwrap().save(&source).unwrap();
+        let mut processor =
+            ImageProcessor::new(settings(&temp_dir.path().join("cache"), vec![Format::Webp]));
+
+        let html = processor
+            .rewrite_html(r#"<img src="pic.webp">"#, |_| {
+                Some((source.clone(), output.clone()))
+            })
+            .unwrap();
+
+        assert_eq!(
+            html,
+            r#"<img src="pic.webp" srcset="pic-webp-40w.webp 40w, pic-webp-80w.webp 80w, pic.webp 100w" sizes="100vw" width="100" height="50" loading="lazy" decoding="async" />"#
+        );
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        assert_eq!(
+            parse_attributes(r#"<img src='a b.png' alt="x > y" hidden width=10/>"#),
+            vec![
+                ("src".to_string(), Some("a b.png".to_string())),
+                ("alt".to_string(), Some("x > y".to_string())),
+                ("hidden".to_string(), None),
+                ("width".to_string(), Some("10".to_string())),
+            ]
+        );
+    }
+}
@@ -4,8 +4,14 @@ pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod content;
+pub mod data;
 pub mod error;
 pub mod generator;
+pub mod helpers;
+pub mod i18n;
+pub mod images;
+pub mod markdown;
+pub mod shortcodes;
 pub mod templates;
 
 pub use error::Result;
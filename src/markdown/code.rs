@@ -0,0 +1,229 @@
+//! Fenced code block attributes: file name header, line numbers and
+//! highlighted lines.
+//!
+//! The info string is `lang key="value" flag …`, e.g.
+//! ```` ```rust title="main.rs" linenos hl_lines="3-5" ````. Blocks without
+//! attributes are left for `pulldown_cmark` to render as usual.
+
+use super::escape_html;
+use pulldown_cmark::{CodeBlockKind, Event, Tag, TagEnd};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Attributes parsed from a fenced code block info string.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CodeAttributes {
+    language: Option<String>,
+    title: Option<String>,
+    line_numbers: bool,
+    highlighted: BTreeSet<usize>,
+}
+
+impl CodeAttributes {
+    fn parse(info: &str) -> Self {
+        let mut attributes = Self::default();
+        let mut tokens = tokenize(info).into_iter().peekable();
+
+        // Первое слово без `=` — язык
+        if let Some((key, None)) = tokens.peek() {
+            attributes.language = Some(key.clone());
+            tokens.next();
+        }
+
+        for (key, value) in tokens {
+            match (key.as_str(), value) {
+                ("title", Some(title)) => attributes.title = Some(title),
+                ("linenos", None) => attributes.line_numbers = true,
+                ("linenos", Some(value)) => attributes.line_numbers = value != "false",
+                ("hl_lines", Some(ranges)) => attributes.highlighted = parse_ranges(&ranges),
+                _ => {}
+            }
+        }
+
+        attributes
+    }
+
+    fn is_plain(&self) -> bool {
+        self.title.is_none() && !self.line_numbers && self.highlighted.is_empty()
+    }
+}
+
+/// Splits an info string into `key`, `key=value` and `key="quoted value"`
+/// tokens.
+fn tokenize(info: &str) -> Vec<(String, Option<String>)> {
+    let mut tokens = Vec::new();
+    let mut chars = info.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != '=') {
+            key.push(c);
+        }
+
+        let value = chars.next_if_eq(&'=').map(|_| {
+            let mut value = String::new();
+            if chars.next_if_eq(&'"').is_some() {
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+            value
+        });
+
+        tokens.push((key, value));
+    }
+
+    tokens
+}
+
+/// Parses line lists like `1 3-5` or `1,3-5`. Malformed parts are skipped.
+fn parse_ranges(ranges: &str) -> BTreeSet<usize> {
+    ranges
+        .split([',', ' '])
+        .filter_map(|part| match part.split_once('-') {
+            Some((start, end)) => Some(start.trim().parse().ok()?..=end.trim().parse().ok()?),
+            None => part.trim().parse().ok().map(|line| line..=line),
+        })
+        .flatten()
+        .collect()
+}
+
+/// Replaces fenced code blocks that carry attributes with rendered HTML.
+pub fn apply(events: Vec<Event<'_>>) -> Vec<Event<'_>> {
+    let mut result = Vec::with_capacity(events.len());
+    let mut block: Option<(CodeAttributes, String)> = None;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(ref info))) => {
+                let attributes = CodeAttributes::parse(info);
+                if attributes.is_plain() {
+                    result.push(event);
+                } else {
+                    block = Some((attributes, String::new()));
+                }
+            }
+            Event::Text(ref text) if block.is_some() => {
+                if let Some((_, code)) = block.as_mut() {
+                    code.push_str(text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) if block.is_some() => {
+                if let Some((attributes, code)) = block.take() {
+                    result.push(Event::Html(render(&attributes, &code).into()));
+                }
+            }
+            event => result.push(event),
+        }
+    }
+
+    result
+}
+
+fn render(attributes: &CodeAttributes, code: &str) -> String {
+    let mut html = String::from("<div class=\"code-block\">");
+    if let Some(title) = &attributes.title {
+        let _ = write!(
+            html,
+            "<div class=\"code-title\">{}</div>",
+            escape_html(title)
+        );
+    }
+
+    html.push_str(if attributes.line_numbers {
+        "<pre class=\"linenos\">"
+    } else {
+        "<pre>"
+    });
+    match &attributes.language {
+        Some(language) => {
+            let _ = write!(html, "<code class=\"language-{}\">", escape_html(language));
+        }
+        None => html.push_str("<code>"),
+    }
+
+    // Номера строк выводятся через CSS из `data-line`, чтобы не попадать
+    // в скопированный текст
+    for (index, line) in code.lines().enumerate() {
+        let number = index + 1;
+        let class = if attributes.highlighted.contains(&number) {
+            "line highlighted"
+        } else {
+            "line"
+        };
+        let _ = writeln!(
+            html,
+            "<span class=\"{class}\" data-line=\"{number}\">{}</span>",
+            escape_html(line)
+        );
+    }
+
+    html.push_str("</code></pre></div>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Parser, html};
+
+    fn render_markdown(markdown: &str) -> String {
+        let events = apply(Parser::new(markdown).collect());
+        let mut output = String::new();
+        html::push_html(&mut output, events.into_iter());
+        output
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let attributes =
+            CodeAttributes::parse(r#"rust title="src/main file.rs" linenos hl_lines="1,3-4 9""#);
+
+        assert_eq!(attributes.language.as_deref(), Some("rust"));
+        assert_eq!(attributes.title.as_deref(), Some("src/main file.rs"));
+        assert!(attributes.line_numbers);
+        assert_eq!(
+            attributes.highlighted.into_iter().collect::<Vec<_>>(),
+            vec![1, 3, 4, 9]
+        );
+
+        let attributes = CodeAttributes::parse("title=notes.txt");
+        assert_eq!(attributes.language, None);
+        assert_eq!(attributes.title.as_deref(), Some("notes.txt"));
+    }
+
+    #[test]
+    fn test_rich_code_block() {
+        let markdown = "```rust title=\"main.rs\" linenos hl_lines=\"2\"\nfn main() {\n    println!(\"<hi>\");\n}\n```\n";
+
+        assert_eq!(
+            render_markdown(markdown),
+            "<div class=\"code-block\"><div class=\"code-title\">main.rs</div>\
+             <pre class=\"linenos\"><code class=\"language-rust\">\
+             <span class=\"line\" data-line=\"1\">fn main() {</span>\n\
+             <span class=\"line highlighted\" data-line=\"2\">    println!(&quot;&lt;hi&gt;&quot;);</span>\n\
+             <span class=\"line\" data-line=\"3\">}</span>\n\
+             </code></pre></div>\n"
+        );
+    }
+
+    #[test]
+    fn test_plain_code_block_untouched() {
+        assert_eq!(
+            render_markdown("```rust\nlet x = 1;\n```\n"),
+            "<pre><code class=\"language-rust\">let x = 1;\n</code></pre>\n"
+        );
+    }
+}
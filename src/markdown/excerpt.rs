@@ -0,0 +1,219 @@
+//! Post excerpts and plain-text summaries.
+//!
+//! Excerpts are cut on the rendered event stream, so they always end on a
+//! block boundary (or at the separator) with every open tag closed.
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+/// Removes the excerpt separator from `events` and returns the events of the
+/// excerpt: everything before the separator, or the first `length` top-level
+/// blocks when `auto` is set (the whole document if it is shorter). Returns
+/// `None` when there is no excerpt.
+pub fn split<'a>(
+    events: &mut [Event<'a>],
+    separator: &str,
+    auto: bool,
+    length: usize,
+) -> Option<Vec<Event<'a>>> {
+    if let Some(excerpt) = cut_at_separator(events, separator) {
+        return Some(excerpt);
+    }
+    if !auto || length == 0 {
+        return None;
+    }
+
+    let mut depth = 0usize;
+    let mut blocks = 0;
+    for (index, event) in events.iter().enumerate() {
+        let ends_block = match event {
+            Event::Start(_) => {
+                depth += 1;
+                false
+            }
+            Event::End(end) => {
+                depth = depth.saturating_sub(1);
+                depth == 0 && !matches!(end, TagEnd::Image | TagEnd::Link)
+            }
+            // Готовые блоки (код с атрибутами, подписи к картинкам)
+            Event::Html(html) => depth == 0 && html.ends_with('\n'),
+            Event::Rule => depth == 0,
+            _ => false,
+        };
+
+        if ends_block {
+            blocks += 1;
+            if blocks == length {
+                return Some(events[..=index].to_vec());
+            }
+        }
+    }
+
+    Some(events.to_vec())
+}
+
+/// Finds `separator` in text or raw HTML outside code blocks. The excerpt is
+/// everything before it with open tags closed; the separator itself is
+/// removed from `events`.
+fn cut_at_separator<'a>(events: &mut [Event<'a>], separator: &str) -> Option<Vec<Event<'a>>> {
+    if separator.is_empty() {
+        return None;
+    }
+
+    let mut open: Vec<Tag<'a>> = Vec::new();
+    for index in 0..events.len() {
+        match &events[index] {
+            Event::Start(tag) => open.push(tag.clone()),
+            Event::End(_) => {
+                open.pop();
+            }
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text)
+                if !open.iter().any(|tag| matches!(tag, Tag::CodeBlock(_))) =>
+            {
+                let Some(position) = text.find(separator) else {
+                    continue;
+                };
+                let before = text[..position].to_string();
+                let without = format!("{before}{}", &text[position + separator.len()..]);
+
+                let mut excerpt = events[..index].to_vec();
+                if !before.trim().is_empty() {
+                    excerpt.push(with_text(&events[index], before));
+                }
+                excerpt.extend(open.iter().rev().map(|tag| Event::End(tag.to_end())));
+
+                events[index] = with_text(&events[index], without);
+                return Some(excerpt);
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn with_text<'a>(event: &Event<'a>, text: String) -> Event<'a> {
+    match event {
+        Event::Html(_) => Event::Html(text.into()),
+        Event::InlineHtml(_) => Event::InlineHtml(text.into()),
+        _ => Event::Text(text.into()),
+    }
+}
+
+/// Collects the readable text of `events`, skipping headings, code blocks
+/// and raw HTML, and truncates it to `length` characters on a word boundary.
+pub fn summary(events: &[Event<'_>], length: usize) -> String {
+    let mut text = String::new();
+    let mut skip = 0usize;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::Heading { .. } | Tag::CodeBlock(_) | Tag::Image { .. }) => skip += 1,
+            Event::End(TagEnd::Heading(_) | TagEnd::CodeBlock | TagEnd::Image) => {
+                skip = skip.saturating_sub(1);
+            }
+            Event::Text(part) | Event::Code(part) if skip == 0 => text.push_str(part),
+            Event::End(
+                TagEnd::Emphasis
+                | TagEnd::Strong
+                | TagEnd::Strikethrough
+                | TagEnd::Superscript
+                | TagEnd::Subscript
+                | TagEnd::Link,
+            ) => {}
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    truncate(
+        &text.split_whitespace().collect::<Vec<_>>().join(" "),
+        length,
+    )
+}
+
+/// Shortens `text` to at most `length` characters plus an ellipsis, cutting
+/// at the last whole word.
+pub fn truncate(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+
+    let prefix: String = text.chars().take(length).collect();
+    let at_word_end = text.chars().nth(length).is_some_and(char::is_whitespace);
+    let cut = match prefix.rfind(' ') {
+        Some(space) if !at_word_end => &prefix[..space],
+        _ => &prefix,
+    };
+    let cut = cut.trim_end_matches(|c: char| c.is_ascii_punctuation() || c == '—' || c == '–');
+
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Parser, html};
+
+    fn excerpt(markdown: &str, auto: bool, length: usize) -> (Option<String>, String) {
+        let mut events: Vec<Event> = Parser::new(markdown).collect();
+        let excerpt = split(&mut events, "<!-- more -->", auto, length).map(|events| {
+            let mut output = String::new();
+            html::push_html(&mut output, events.into_iter());
+            output
+        });
+        let mut full = String::new();
+        html::push_html(&mut full, events.into_iter());
+        (excerpt, full)
+    }
+
+    #[test]
+    fn test_separator_inside_line() {
+        let (excerpt, full) = excerpt("- one\n- two <!-- more --> three\n- four\n", true, 1);
+
+        assert_eq!(
+            excerpt.as_deref(),
+            Some("<ul>\n<li>one</li>\n<li>two </li>\n</ul>\n")
+        );
+        assert_eq!(
+            full,
+            "<ul>\n<li>one</li>\n<li>two  three</li>\n<li>four</li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn test_auto_excerpt_keeps_blocks_whole() {
+        let markdown = "Intro\n\n```\ncode\n\nmore code\n```\n\nOutro\n";
+
+        assert_eq!(
+            excerpt(markdown, true, 2).0.as_deref(),
+            Some("<p>Intro</p>\n<pre><code>code\n\nmore code\n</code></pre>\n")
+        );
+        assert_eq!(excerpt(markdown, false, 2).0, None);
+        assert_eq!(
+            excerpt(markdown, true, 5).0,
+            Some(excerpt(markdown, true, 3).1)
+        );
+    }
+
+    #[test]
+    fn test_separator_in_code_ignored() {
+        let (excerpt, _) = excerpt("```\n<!-- more -->\n```\n\nText\n", false, 3);
+
+        assert_eq!(excerpt, None);
+    }
+
+    #[test]
+    fn test_summary() {
+        let events: Vec<Event> = Parser::new(
+            "# Title\n\nHello, *world*.\nSecond `line`, with more words ![alt](a.png)\n",
+        )
+        .collect();
+
+        assert_eq!(
+            summary(&events, 100),
+            "Hello, world. Second line, with more words"
+        );
+        assert_eq!(summary(&events, 20), "Hello, world. Second…");
+        assert_eq!(summary(&events, 15), "Hello, world…");
+    }
+}
@@ -0,0 +1,830 @@
+//! Build-time `TeX` to `MathML` conversion for `$…$` and `$$…$$` formulas.
+//!
+//! Covers the subset of LaTeX math used in technical writing: scripts,
+//! fractions, roots, Greek letters and common symbols, functions, accents,
+//! font commands, `\left…\right` delimiters and matrix-like environments.
+
+use super::escape_html;
+use std::fmt::Write;
+
+/// Converts a TeX formula to a `<math>` element.
+///
+/// The original source is kept as an `application/x-tex` annotation so it
+/// survives copy and paste.
+///
+/// # Errors
+///
+/// Returns a description of the problem if the formula is not valid TeX or
+/// uses an unsupported command.
+pub fn to_mathml(tex: &str, display: bool) -> Result<String, String> {
+    let mut parser = MathParser::new(tex, display);
+    let (items, stop) = parser.parse_sequence()?;
+    if !matches!(stop, Stop::Eof) {
+        return Err(stop.unexpected());
+    }
+
+    let display_attr = if display { r#" display="block""# } else { "" };
+    Ok(format!(
+        r#"<math xmlns="http://www.w3.org/1998/Math/MathML"{display_attr}><semantics>{}<annotation encoding="application/x-tex">{}</annotation></semantics></math>"#,
+        mrow(items),
+        escape_html(tex.trim())
+    ))
+}
+
+/// What ended a sequence of atoms.
+enum Stop {
+    Eof,
+    Brace,
+    Ampersand,
+    RowBreak,
+    Right,
+    End(String),
+}
+
+impl Stop {
+    fn unexpected(&self) -> String {
+        match self {
+            Self::Eof => "Unexpected end of formula".to_string(),
+            Self::Brace => "Unexpected '}'".to_string(),
+            Self::Ampersand => "Unexpected '&' outside of an environment".to_string(),
+            Self::RowBreak => "Unexpected '\\\\' outside of an environment".to_string(),
+            Self::Right => "Unexpected '\\right' without '\\left'".to_string(),
+            Self::End(name) => format!("Unexpected '\\end{{{name}}}'"),
+        }
+    }
+}
+
+/// A rendered element together with how it takes scripts.
+struct Atom {
+    mathml: String,
+    /// Scripts go above and below (e.g. `\sum` in display mode)
+    limits: bool,
+}
+
+impl Atom {
+    const fn new(mathml: String) -> Self {
+        Self {
+            mathml,
+            limits: false,
+        }
+    }
+}
+
+struct MathParser<'a> {
+    source: &'a str,
+    position: usize,
+    display: bool,
+    /// `mathvariant` applied to identifiers by font commands
+    variant: Option<&'static str>,
+}
+
+impl<'a> MathParser<'a> {
+    const fn new(source: &'a str, display: bool) -> Self {
+        Self {
+            source,
+            position: 0,
+            display,
+            variant: None,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(format!("Expected '{expected}'")),
+        }
+    }
+
+    /// Reads a command name after `\`: a run of letters or a single symbol.
+    fn command_name(&mut self) -> Result<String, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.bump();
+        }
+        if self.position == start {
+            self.bump().ok_or("Unexpected '\\' at end of formula")?;
+        }
+        Ok(self.source[start..self.position].to_string())
+    }
+
+    /// Parses atoms until a closing token, which is returned to the caller.
+    fn parse_sequence(&mut self) -> Result<(Vec<Atom>, Stop), String> {
+        let mut items: Vec<Atom> = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            let Some(c) = self.peek() else {
+                return Ok((items, Stop::Eof));
+            };
+
+            match c {
+                '}' => {
+                    self.bump();
+                    return Ok((items, Stop::Brace));
+                }
+                '&' => {
+                    self.bump();
+                    return Ok((items, Stop::Ampersand));
+                }
+                '^' | '_' => {
+                    let base = items
+                        .pop()
+                        .unwrap_or_else(|| Atom::new("<mrow></mrow>".to_string()));
+                    items.push(self.parse_scripts(base)?);
+                }
+                '\\' => {
+                    let checkpoint = self.position;
+                    self.bump();
+                    match self.command_name()?.as_str() {
+                        "\\" => return Ok((items, Stop::RowBreak)),
+                        "right" => return Ok((items, Stop::Right)),
+                        "end" => {
+                            let name = self.braced_text()?;
+                            return Ok((items, Stop::End(name)));
+                        }
+                        _ => {
+                            self.position = checkpoint;
+                            items.push(self.parse_atom()?);
+                        }
+                    }
+                }
+                _ => items.push(self.parse_atom()?),
+            }
+        }
+    }
+
+    /// Parses `^` and `_` following `base`, in any order.
+    fn parse_scripts(&mut self, base: Atom) -> Result<Atom, String> {
+        let mut sub = None;
+        let mut sup = None;
+
+        loop {
+            self.skip_whitespace();
+            let slot = match self.peek() {
+                Some('_') => &mut sub,
+                Some('^') => &mut sup,
+                _ => break,
+            };
+            if slot.is_some() {
+                return Err("Double subscript or superscript".to_string());
+            }
+            self.bump();
+            *slot = Some(self.parse_argument()?);
+        }
+
+        let (under, over, both) = if base.limits && self.display {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+        let base = base.mathml;
+        let mathml = match (sub, sup) {
+            (Some(sub), Some(sup)) => format!("<{both}>{base}{sub}{sup}</{both}>"),
+            (Some(sub), None) => format!("<{under}>{base}{sub}</{under}>"),
+            (None, Some(sup)) => format!("<{over}>{base}{sup}</{over}>"),
+            (None, None) => base,
+        };
+        Ok(Atom::new(mathml))
+    }
+
+    /// Parses a command argument: a braced group or a single token.
+    fn parse_argument(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None | Some('}' | '&' | '^' | '_') => Err("Missing argument".to_string()),
+            _ => self.parse_atom().map(|atom| atom.mathml),
+        }
+    }
+
+    fn parse_group(&mut self) -> Result<String, String> {
+        let (items, stop) = self.parse_sequence()?;
+        match stop {
+            Stop::Brace => Ok(mrow(items)),
+            Stop::Eof => Err("Missing closing '}'".to_string()),
+            stop => Err(stop.unexpected()),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Atom, String> {
+        let c = self.bump().ok_or("Unexpected end of formula")?;
+
+        let mathml = match c {
+            '{' => self.parse_group()?,
+            '\\' => return self.parse_command(),
+            '0'..='9' | '.' if c != '.' || self.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                let mut number = c.to_string();
+                while let Some(next) = self.peek() {
+                    let is_decimal_point = next == '.'
+                        && self.source[self.position + 1..]
+                            .starts_with(|c: char| c.is_ascii_digit());
+                    if !(next.is_ascii_digit() || is_decimal_point) {
+                        break;
+                    }
+                    number.push(next);
+                    self.bump();
+                }
+                format!("<mn>{number}</mn>")
+            }
+            '-' => "<mo>−</mo>".to_string(),
+            '\'' => "<mo>′</mo>".to_string(),
+            '~' => r#"<mspace width="0.333em"></mspace>"#.to_string(),
+            '#' | '$' | '%' => return Err(format!("Unexpected '{c}'")),
+            c if c.is_alphabetic() => self.identifier(&c.to_string()),
+            c => format!("<mo>{}</mo>", escape_html(&c.to_string())),
+        };
+        Ok(Atom::new(mathml))
+    }
+
+    fn identifier(&self, text: &str) -> String {
+        self.variant.map_or_else(
+            || format!("<mi>{text}</mi>"),
+            |variant| format!(r#"<mi mathvariant="{variant}">{text}</mi>"#),
+        )
+    }
+
+    fn parse_command(&mut self) -> Result<Atom, String> {
+        let name = self.command_name()?;
+
+        let mathml = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_argument()?;
+                let denominator = self.parse_argument()?;
+                format!("<mfrac>{numerator}{denominator}</mfrac>")
+            }
+            "binom" => {
+                let n = self.parse_argument()?;
+                let k = self.parse_argument()?;
+                format!(
+                    r#"<mrow><mo>(</mo><mfrac linethickness="0">{n}{k}</mfrac><mo>)</mo></mrow>"#
+                )
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                if self.peek() == Some('[') {
+                    self.bump();
+                    let index = self.parse_until(']')?;
+                    let radicand = self.parse_argument()?;
+                    format!("<mroot>{radicand}{index}</mroot>")
+                } else {
+                    format!("<msqrt>{}</msqrt>", self.parse_argument()?)
+                }
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => {
+                format!("<mtext>{}</mtext>", escape_html(&self.braced_text()?))
+            }
+            "operatorname" => format!("<mi>{}</mi>", escape_html(&self.braced_text()?)),
+            "left" => self.parse_left()?,
+            "begin" => self.parse_environment()?,
+            "not" => {
+                let negated = self.parse_argument()?;
+                format!("<mrow>{negated}<mo>&#x338;</mo></mrow>")
+            }
+            _ => {
+                if let Some(variant) = lookup(FONTS, &name) {
+                    let outer = self.variant.replace(variant);
+                    let argument = self.parse_argument();
+                    self.variant = outer;
+                    argument?
+                } else if let Some(accent) = lookup(ACCENTS, &name) {
+                    let base = self.parse_argument()?;
+                    if name == "underline" {
+                        format!(r#"<munder accentunder="true">{base}<mo>{accent}</mo></munder>"#)
+                    } else {
+                        format!(r#"<mover accent="true">{base}<mo>{accent}</mo></mover>"#)
+                    }
+                } else {
+                    return symbol(&name).ok_or_else(|| format!("Unknown command '\\{name}'"));
+                }
+            }
+        };
+        Ok(Atom::new(mathml))
+    }
+
+    /// Parses atoms up to `end`, used for the optional `\sqrt[n]` index.
+    fn parse_until(&mut self, end: char) -> Result<String, String> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(c) if c == end => {
+                    self.bump();
+                    return Ok(mrow(items));
+                }
+                None => return Err(format!("Missing '{end}'")),
+                _ => items.push(self.parse_atom()?),
+            }
+        }
+    }
+
+    /// Reads the raw contents of a `{…}` group, e.g. for `\text`.
+    fn braced_text(&mut self) -> Result<String, String> {
+        self.expect('{')?;
+        let start = self.position;
+        let mut depth = 0;
+        while let Some(c) = self.bump() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(self.source[start..self.position - 1].to_string()),
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        Err("Missing closing '}'".to_string())
+    }
+
+    fn parse_left(&mut self) -> Result<String, String> {
+        let open = self.delimiter()?;
+        let (items, stop) = self.parse_sequence()?;
+        match stop {
+            Stop::Right => {}
+            Stop::Eof => return Err("Missing '\\right'".to_string()),
+            stop => return Err(stop.unexpected()),
+        }
+        let close = self.delimiter()?;
+
+        let mut mathml = String::from("<mrow>");
+        for delimiter in [Some(open), None, Some(close)] {
+            match delimiter {
+                Some("") => {}
+                Some(delimiter) => {
+                    let _ = write!(mathml, r#"<mo stretchy="true">{delimiter}</mo>"#);
+                }
+                None => items.iter().for_each(|item| mathml.push_str(&item.mathml)),
+            }
+        }
+        mathml.push_str("</mrow>");
+        Ok(mathml)
+    }
+
+    /// Reads a delimiter after `\left` or `\right`; `.` means none.
+    fn delimiter(&mut self) -> Result<&'static str, String> {
+        self.skip_whitespace();
+        let delimiter = match self.bump() {
+            Some('.') => "",
+            Some('(') => "(",
+            Some(')') => ")",
+            Some('[') => "[",
+            Some(']') => "]",
+            Some('|') => "|",
+            Some('/') => "/",
+            Some('<') => "⟨",
+            Some('>') => "⟩",
+            Some('\\') => {
+                let name = self.command_name()?;
+                lookup(DELIMITERS, &name).ok_or_else(|| format!("Invalid delimiter '\\{name}'"))?
+            }
+            _ => return Err("Missing delimiter".to_string()),
+        };
+        Ok(delimiter)
+    }
+
+    fn parse_environment(&mut self) -> Result<String, String> {
+        let name = self.braced_text()?;
+        let (open, close, align) = match name.as_str() {
+            "matrix" | "smallmatrix" | "gathered" | "gather" | "gather*" => ("", "", None),
+            "pmatrix" => ("(", ")", None),
+            "bmatrix" => ("[", "]", None),
+            "Bmatrix" => ("{", "}", None),
+            "vmatrix" => ("|", "|", None),
+            "Vmatrix" => ("‖", "‖", None),
+            "cases" => ("{", "", Some("left left")),
+            "aligned" | "align" | "align*" | "split" => ("", "", Some("right left")),
+            _ => return Err(format!("Unknown environment '{name}'")),
+        };
+
+        let mut rows: Vec<Vec<String>> = vec![Vec::new()];
+        loop {
+            let (items, stop) = self.parse_sequence()?;
+            let row = rows.last_mut().expect("rows always has a current row");
+            row.push(mrow(items));
+            match stop {
+                Stop::Ampersand => {}
+                Stop::RowBreak => rows.push(Vec::new()),
+                Stop::End(end) if end == name => break,
+                Stop::Eof => return Err(format!("Missing '\\end{{{name}}}'")),
+                stop => return Err(stop.unexpected()),
+            }
+        }
+        // Завершающий `\\` не создает пустую строку
+        if rows.len() > 1 && rows.last().is_some_and(|row| row == &["<mrow></mrow>"]) {
+            rows.pop();
+        }
+
+        let mut mathml = String::from("<mrow>");
+        if !open.is_empty() {
+            let _ = write!(mathml, r#"<mo stretchy="true">{open}</mo>"#);
+        }
+        match align {
+            Some(align) => {
+                let _ = write!(mathml, r#"<mtable columnalign="{align}">"#);
+            }
+            None => mathml.push_str("<mtable>"),
+        }
+        for row in rows {
+            mathml.push_str("<mtr>");
+            for cell in row {
+                let _ = write!(mathml, "<mtd>{cell}</mtd>");
+            }
+            mathml.push_str("</mtr>");
+        }
+        mathml.push_str("</mtable>");
+        if !close.is_empty() {
+            let _ = write!(mathml, r#"<mo stretchy="true">{close}</mo>"#);
+        }
+        mathml.push_str("</mrow>");
+        Ok(mathml)
+    }
+}
+
+/// Joins atoms into a single element, avoiding redundant `<mrow>`s.
+fn mrow(mut items: Vec<Atom>) -> String {
+    if items.len() == 1 {
+        return items.pop().map(|atom| atom.mathml).unwrap_or_default();
+    }
+    let mut mathml = String::from("<mrow>");
+    for item in items {
+        mathml.push_str(&item.mathml);
+    }
+    mathml.push_str("</mrow>");
+    mathml
+}
+
+fn lookup(table: &[(&str, &'static str)], name: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map(|(_, value)| *value)
+}
+
+fn symbol(name: &str) -> Option<Atom> {
+    if let Some(text) = lookup(IDENTIFIERS, name) {
+        return Some(Atom::new(format!("<mi>{text}</mi>")));
+    }
+    if let Some(text) = lookup(UPRIGHT_IDENTIFIERS, name) {
+        return Some(Atom::new(format!(
+            r#"<mi mathvariant="normal">{text}</mi>"#
+        )));
+    }
+    if let Some(text) = lookup(OPERATORS, name) {
+        return Some(Atom::new(format!("<mo>{text}</mo>")));
+    }
+    if let Some(width) = lookup(SPACES, name) {
+        return Some(Atom::new(format!(r#"<mspace width="{width}"></mspace>"#)));
+    }
+    if let Some(text) = lookup(LARGE_OPERATORS, name) {
+        return Some(Atom {
+            mathml: format!(r#"<mo largeop="true">{text}</mo>"#),
+            // Пределы интегралов остаются справа, как в LaTeX
+            limits: !matches!(name, "int" | "iint" | "iiint" | "oint"),
+        });
+    }
+    if FUNCTIONS.contains(&name) {
+        return Some(Atom {
+            mathml: format!("<mi>{name}</mi>"),
+            limits: LIMIT_FUNCTIONS.contains(&name),
+        });
+    }
+    None
+}
+
+const IDENTIFIERS: &[(&str, &str)] = &[
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("varpi", "ϖ"),
+    ("rho", "ρ"),
+    ("varrho", "ϱ"),
+    ("sigma", "σ"),
+    ("varsigma", "ς"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("ell", "ℓ"),
+    ("hbar", "ℏ"),
+    ("imath", "ı"),
+    ("jmath", "ȷ"),
+];
+
+const UPRIGHT_IDENTIFIERS: &[(&str, &str)] = &[
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    ("infty", "∞"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("emptyset", "∅"),
+    ("varnothing", "∅"),
+    ("aleph", "ℵ"),
+    ("Re", "ℜ"),
+    ("Im", "ℑ"),
+    ("wp", "℘"),
+    ("top", "⊤"),
+    ("bot", "⊥"),
+    ("angle", "∠"),
+    ("triangle", "△"),
+    ("prime", "′"),
+];
+
+const OPERATORS: &[(&str, &str)] = &[
+    ("cdot", "⋅"),
+    ("times", "×"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("ast", "∗"),
+    ("star", "⋆"),
+    ("circ", "∘"),
+    ("bullet", "∙"),
+    ("oplus", "⊕"),
+    ("ominus", "⊖"),
+    ("otimes", "⊗"),
+    ("odot", "⊙"),
+    ("le", "≤"),
+    ("leq", "≤"),
+    ("ge", "≥"),
+    ("geq", "≥"),
+    ("ne", "≠"),
+    ("neq", "≠"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("simeq", "≃"),
+    ("cong", "≅"),
+    ("propto", "∝"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("gets", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("implies", "⟹"),
+    ("impliedby", "⟸"),
+    ("iff", "⟺"),
+    ("mapsto", "↦"),
+    ("uparrow", "↑"),
+    ("downarrow", "↓"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("ni", "∋"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("wedge", "∧"),
+    ("land", "∧"),
+    ("vee", "∨"),
+    ("lor", "∨"),
+    ("neg", "¬"),
+    ("lnot", "¬"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("nexists", "∄"),
+    ("mid", "∣"),
+    ("parallel", "∥"),
+    ("perp", "⊥"),
+    ("vdash", "⊢"),
+    ("models", "⊨"),
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("cdots", "⋯"),
+    ("vdots", "⋮"),
+    ("ddots", "⋱"),
+    ("colon", ":"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("{", "{"),
+    ("}", "}"),
+    ("|", "‖"),
+    ("vert", "|"),
+    ("Vert", "‖"),
+    ("_", "_"),
+    ("%", "%"),
+    ("$", "$"),
+    ("#", "#"),
+    ("&", "&amp;"),
+];
+
+const LARGE_OPERATORS: &[(&str, &str)] = &[
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("coprod", "∐"),
+    ("bigcup", "⋃"),
+    ("bigcap", "⋂"),
+    ("bigoplus", "⨁"),
+    ("bigotimes", "⨂"),
+    ("bigvee", "⋁"),
+    ("bigwedge", "⋀"),
+    ("int", "∫"),
+    ("iint", "∬"),
+    ("iiint", "∭"),
+    ("oint", "∮"),
+];
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "coth", "log", "ln", "lg", "exp", "det", "dim", "ker", "deg", "gcd", "hom", "arg", "max",
+    "min", "sup", "inf", "lim", "liminf", "limsup", "Pr",
+];
+
+/// Functions that take limits underneath in display mode.
+const LIMIT_FUNCTIONS: &[&str] = &[
+    "max", "min", "sup", "inf", "lim", "liminf", "limsup", "det", "gcd", "Pr",
+];
+
+const SPACES: &[(&str, &str)] = &[
+    (",", "0.167em"),
+    (":", "0.222em"),
+    (">", "0.222em"),
+    (";", "0.278em"),
+    ("!", "-0.167em"),
+    (" ", "0.333em"),
+    ("quad", "1em"),
+    ("qquad", "2em"),
+];
+
+const FONTS: &[(&str, &str)] = &[
+    ("mathrm", "normal"),
+    ("mathbf", "bold"),
+    ("mathit", "italic"),
+    ("mathbb", "double-struck"),
+    ("mathcal", "script"),
+    ("mathscr", "script"),
+    ("mathfrak", "fraktur"),
+    ("mathsf", "sans-serif"),
+    ("mathtt", "monospace"),
+    ("boldsymbol", "bold-italic"),
+];
+
+const ACCENTS: &[(&str, &str)] = &[
+    ("hat", "^"),
+    ("widehat", "^"),
+    ("bar", "¯"),
+    ("overline", "¯"),
+    ("vec", "→"),
+    ("overrightarrow", "→"),
+    ("tilde", "~"),
+    ("widetilde", "~"),
+    ("dot", "˙"),
+    ("ddot", "¨"),
+    ("underline", "_"),
+];
+
+const DELIMITERS: &[(&str, &str)] = &[
+    ("{", "{"),
+    ("}", "}"),
+    ("|", "‖"),
+    ("vert", "|"),
+    ("Vert", "‖"),
+    ("lvert", "|"),
+    ("rvert", "|"),
+    ("lVert", "‖"),
+    ("rVert", "‖"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strips the `<math>` wrapper and annotation for compact assertions.
+    fn body(tex: &str) -> String {
+        let mathml = to_mathml(tex, false).unwrap();
+        let start = mathml.find("<semantics>").unwrap() + "<semantics>".len();
+        let end = mathml.find("<annotation").unwrap();
+        mathml[start..end].to_string()
+    }
+
+    #[test]
+    fn test_scripts_and_numbers() {
+        assert_eq!(
+            body("x^2 + y_{i,j} - 3.14"),
+            "<mrow><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><msub><mi>y</mi><mrow><mi>i</mi><mo>,</mo><mi>j</mi></mrow></msub><mo>−</mo><mn>3.14</mn></mrow>"
+        );
+    }
+
+    #[test]
+    fn test_fraction_root_and_symbols() {
+        assert_eq!(
+            body(r"\frac{\alpha}{\sqrt[3]{2}} \le \infty"),
+            r#"<mrow><mfrac><mi>α</mi><mroot><mn>2</mn><mn>3</mn></mroot></mfrac><mo>≤</mo><mi mathvariant="normal">∞</mi></mrow>"#
+        );
+    }
+
+    #[test]
+    fn test_display_limits() {
+        let mathml = to_mathml(r"\sum_{i=1}^n i", true).unwrap();
+        assert!(mathml.contains(r#"display="block""#));
+        assert!(mathml.contains(r#"<munderover><mo largeop="true">∑</mo>"#));
+        assert!(
+            mathml.contains(
+                r#"<annotation encoding="application/x-tex">\sum_{i=1}^n i</annotation>"#
+            )
+        );
+
+        assert!(body(r"\sum_{i=1}^n i").starts_with("<mrow><msubsup>"));
+    }
+
+    #[test]
+    fn test_left_right_and_environments() {
+        assert_eq!(
+            body(r"\left( \mathbb{R} \right]"),
+            r#"<mrow><mo stretchy="true">(</mo><mi mathvariant="double-struck">R</mi><mo stretchy="true">]</mo></mrow>"#
+        );
+        assert_eq!(
+            body(r"\begin{pmatrix} a & b \\ c & d \\ \end{pmatrix}"),
+            r#"<mrow><mo stretchy="true">(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr><mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo stretchy="true">)</mo></mrow>"#
+        );
+    }
+
+    #[test]
+    fn test_text_is_escaped() {
+        assert_eq!(
+            body(r"\text{a < b} \sin x"),
+            "<mrow><mtext>a &lt; b</mtext><mi>sin</mi><mi>x</mi></mrow>"
+        );
+    }
+
+    #[test]
+    fn test_invalid_tex() {
+        assert_eq!(
+            to_mathml(r"\foo + 1", false).unwrap_err(),
+            r"Unknown command '\foo'"
+        );
+        assert_eq!(
+            to_mathml(r"\frac{1}{2", false).unwrap_err(),
+            "Missing closing '}'"
+        );
+        assert_eq!(to_mathml("a}", false).unwrap_err(), "Unexpected '}'");
+        assert_eq!(
+            to_mathml("x^2^3", false).unwrap_err(),
+            "Double subscript or superscript"
+        );
+        assert_eq!(
+            to_mathml(r"\begin{matrix} a", false).unwrap_err(),
+            r"Missing '\end{matrix}'"
+        );
+        assert_eq!(
+            to_mathml(r"\left( x", false).unwrap_err(),
+            r"Missing '\right'"
+        );
+    }
+}
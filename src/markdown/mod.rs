@@ -0,0 +1,862 @@
+//! Markdown rendering pipeline for post content.
+//!
+//! Posts are parsed into `pulldown_cmark` events, passed through a series of
+//! transformations driven by [`RenderOptions`] and then written out as HTML.
+
+use crate::config::Config;
+use pulldown_cmark::{CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd, html};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+mod code;
+mod excerpt;
+mod math;
+mod typography;
+
+pub use excerpt::truncate;
+
+/// Rendering settings derived from the `[markdown]` and `[content]` config sections.
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RenderOptions {
+    /// Honour `{#id .class}` heading attributes
+    pub heading_attributes: bool,
+    /// Parse `$…$` and `$$…$$` as TeX math
+    pub math: bool,
+    /// Append a self-link anchor to every heading
+    pub anchor_links: bool,
+    /// `aria-label` of the heading anchors, in the language of the content
+    pub anchor_label: String,
+    /// Shallowest heading level included in the table of contents
+    pub toc_min_level: u8,
+    /// Deepest heading level included in the table of contents
+    pub toc_max_level: u8,
+    /// Site base URL; absolute links to it are rewritten to root-relative ones
+    pub base_url: String,
+    /// Open links outside `base_url` in a new tab
+    pub external_links_new_tab: bool,
+    /// Add `rel="nofollow"` to external links
+    pub external_links_nofollow: bool,
+    /// Add `rel="ugc"` to external links
+    pub external_links_ugc: bool,
+    /// CSS class added to external links
+    pub external_links_class: Option<String>,
+    /// Render standalone titled images as `<figure>` with a caption
+    pub figures: bool,
+    /// Replace `:shortcode:` sequences with emoji
+    pub emoji: bool,
+    /// Image URL template for emoji (`{code}` is replaced with the hex
+    /// codepoints); Unicode characters are emitted when `None`
+    pub emoji_image_url: Option<String>,
+    /// Language tag selecting quote style and typography rules
+    pub language: String,
+    /// Replace straight quotes with locale-specific ones
+    pub smart_quotes: bool,
+    /// Replace `--`, `---` and `...` and insert non-breaking spaces
+    pub smart_punctuation: bool,
+    /// Marker ending the excerpt; may appear anywhere in a line
+    pub excerpt_separator: String,
+    /// Without a separator, use the first `excerpt_length` blocks as excerpt
+    pub auto_excerpt: bool,
+    /// Number of top-level blocks in an automatic excerpt
+    pub excerpt_length: usize,
+    /// Character budget of the plain-text summary
+    pub summary_length: usize,
+}
+
+/// Default image set for `emoji_images = true`.
+pub const DEFAULT_EMOJI_IMAGE_URL: &str =
+    "https://cdn.jsdelivr.net/gh/jdecked/twemoji@15.1.0/assets/svg/{code}.svg";
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            heading_attributes: false,
+            math: false,
+            anchor_links: false,
+            anchor_label: "Link to this section".to_string(),
+            toc_min_level: 2,
+            toc_max_level: 3,
+            base_url: String::new(),
+            external_links_new_tab: false,
+            external_links_nofollow: false,
+            external_links_ugc: false,
+            external_links_class: None,
+            figures: false,
+            emoji: false,
+            emoji_image_url: None,
+            language: "ru".to_string(),
+            smart_quotes: false,
+            smart_punctuation: false,
+            excerpt_separator: "<!-- more -->".to_string(),
+            auto_excerpt: true,
+            excerpt_length: 3,
+            summary_length: 160,
+        }
+    }
+}
+
+impl RenderOptions {
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        let defaults = Self::default();
+        let markdown = config.markdown.as_ref();
+        let content = config.content.as_ref();
+
+        Self {
+            heading_attributes: markdown
+                .and_then(|m| m.enable_heading_attributes)
+                .unwrap_or(defaults.heading_attributes),
+            math: markdown
+                .and_then(|m| m.enable_math)
+                .unwrap_or(defaults.math),
+            anchor_links: content
+                .and_then(|c| c.add_anchor_links)
+                .unwrap_or(defaults.anchor_links),
+            anchor_label: defaults.anchor_label,
+            toc_min_level: content
+                .and_then(|c| c.toc_min_level)
+                .unwrap_or(defaults.toc_min_level),
+            toc_max_level: content
+                .and_then(|c| c.toc_max_level)
+                .unwrap_or(defaults.toc_max_level),
+            base_url: config.site.base_url.trim_end_matches('/').to_string(),
+            external_links_new_tab: content
+                .and_then(|c| c.external_links_new_tab)
+                .unwrap_or(defaults.external_links_new_tab),
+            external_links_nofollow: content
+                .and_then(|c| c.external_links_nofollow)
+                .unwrap_or(defaults.external_links_nofollow),
+            external_links_ugc: content
+                .and_then(|c| c.external_links_ugc)
+                .unwrap_or(defaults.external_links_ugc),
+            external_links_class: content.and_then(|c| c.external_links_class.clone()),
+            figures: content
+                .and_then(|c| c.image_figures)
+                .unwrap_or(defaults.figures),
+            emoji: content
+                .and_then(|c| c.enable_emoji)
+                .unwrap_or(defaults.emoji),
+            emoji_image_url: content
+                .filter(|c| c.emoji_images.unwrap_or(false))
+                .map(|c| {
+                    c.emoji_image_url
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_EMOJI_IMAGE_URL.to_string())
+                }),
+            language: config.site.language.clone().unwrap_or(defaults.language),
+            smart_quotes: content
+                .and_then(|c| c.enable_smart_quotes)
+                .unwrap_or(defaults.smart_quotes),
+            smart_punctuation: markdown
+                .and_then(|m| m.enable_smart_punctuation)
+                .unwrap_or(defaults.smart_punctuation),
+            excerpt_separator: config.get_excerpt_separator().to_string(),
+            auto_excerpt: config.is_auto_excerpt_enabled(),
+            excerpt_length: config.get_excerpt_length(),
+            summary_length: config.get_summary_length(),
+        }
+    }
+
+    /// Returns a copy of the options for content written in `language`.
+    #[must_use]
+    pub fn with_language(&self, language: &str) -> Self {
+        Self {
+            language: language.to_string(),
+            ..self.clone()
+        }
+    }
+
+    fn parser_options(&self) -> Options {
+        let mut options = Options::empty();
+        options.set(Options::ENABLE_HEADING_ATTRIBUTES, self.heading_attributes);
+        options.set(Options::ENABLE_MATH, self.math);
+        options
+    }
+}
+
+/// A table of contents entry with its nested subheadings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub level: u8,
+    pub id: String,
+    pub text: String,
+    pub children: Vec<Self>,
+}
+
+/// Result of rendering a Markdown document.
+#[derive(Debug, Clone, Default)]
+pub struct Rendered {
+    pub html: String,
+    pub toc: Vec<TocEntry>,
+    /// HTML of the excerpt, empty when the document has none
+    pub excerpt: String,
+    /// Plain-text summary of the excerpt (or the whole document)
+    pub summary: String,
+}
+
+/// Creates a Markdown parser configured for `options`.
+#[must_use]
+pub fn parser<'a>(markdown: &'a str, options: &RenderOptions) -> Parser<'a> {
+    Parser::new_ext(markdown, options.parser_options())
+}
+
+/// A formula that could not be rendered.
+#[derive(Debug)]
+pub struct MathError<'a> {
+    /// What is wrong with the TeX source
+    pub message: String,
+    /// Event to render instead: the source as an inline code span
+    pub fallback: Event<'a>,
+}
+
+/// Renders `InlineMath` and `DisplayMath` events to `MathML`, passing other
+/// events through.
+///
+/// # Errors
+///
+/// Returns a [`MathError`] for invalid TeX. Its `fallback` keeps the post
+/// renderable, so callers usually report the message and carry on.
+pub fn render_math(event: Event<'_>) -> Result<Event<'_>, MathError<'_>> {
+    let (tex, display) = match &event {
+        Event::InlineMath(tex) => (tex, false),
+        Event::DisplayMath(tex) => (tex, true),
+        _ => return Ok(event),
+    };
+
+    math::to_mathml(tex, display)
+        .map(|mathml| Event::InlineHtml(mathml.into()))
+        .map_err(|message| {
+            let delimiter = if display { "$$" } else { "$" };
+            MathError {
+                fallback: Event::InlineHtml(
+                    format!(
+                        r#"<code class="math-error" title="{}">{}</code>"#,
+                        escape_html(&message),
+                        escape_html(&format!("{delimiter}{tex}{delimiter}"))
+                    )
+                    .into(),
+                ),
+                message,
+            }
+        })
+}
+
+/// Renders parsed Markdown events to HTML, applying all content passes.
+pub fn render<'a>(events: impl Iterator<Item = Event<'a>>, options: &RenderOptions) -> Rendered {
+    let mut events = code::apply(merge_text(events));
+
+    if options.emoji {
+        events = replace_emoji(events, options.emoji_image_url.as_deref());
+    }
+    if options.smart_quotes || options.smart_punctuation {
+        typography::apply(
+            &mut events,
+            &options.language,
+            options.smart_quotes,
+            options.smart_punctuation,
+        );
+    }
+    process_links(&mut events, options);
+    if options.figures {
+        events = wrap_figures(&events, options);
+    }
+    let anchor_label = options
+        .anchor_links
+        .then_some(options.anchor_label.as_str());
+    let headings = assign_heading_ids(&mut events, anchor_label);
+    let toc = build_toc(&headings, options.toc_min_level, options.toc_max_level);
+
+    let excerpt_events = excerpt::split(
+        &mut events,
+        &options.excerpt_separator,
+        options.auto_excerpt,
+        options.excerpt_length,
+    );
+    let summary = excerpt::summary(
+        excerpt_events.as_deref().unwrap_or(&events),
+        options.summary_length,
+    );
+    let mut excerpt = String::new();
+    if let Some(excerpt_events) = excerpt_events {
+        html::push_html(&mut excerpt, excerpt_events.into_iter());
+    }
+
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, events.into_iter());
+
+    Rendered {
+        html: html_output,
+        toc,
+        excerpt,
+        summary,
+    }
+}
+
+/// Converts text to a URL slug. Latin and Cyrillic letters and digits are
+/// kept, everything else collapses into single hyphens.
+#[must_use]
+pub fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'а'..='я' | 'ё' | 'a'..='z' | '0'..='9' => c,
+            _ => '-',
+        })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Escapes text for use inside HTML content and attribute values.
+#[must_use]
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Joins adjacent text events, which the parser emits in pieces, so that
+/// text passes see whole runs of text.
+fn merge_text<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut merged: Vec<Event<'a>> = Vec::new();
+
+    for event in events {
+        if let (Some(Event::Text(previous)), Event::Text(text)) = (merged.last_mut(), &event) {
+            *previous = format!("{previous}{text}").into();
+        } else {
+            merged.push(event);
+        }
+    }
+
+    merged
+}
+
+/// Replaces `:shortcode:` sequences in text with emoji. Code spans and code
+/// blocks are left untouched, and unknown shortcodes pass through as is.
+fn replace_emoji<'a>(events: Vec<Event<'a>>, image_url: Option<&str>) -> Vec<Event<'a>> {
+    let mut result = Vec::with_capacity(events.len());
+    let mut in_code_block = false;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(ref text) if !in_code_block && text.contains(':') => {
+                result.extend(emoji_segments(text, image_url));
+                continue;
+            }
+            _ => {}
+        }
+        result.push(event);
+    }
+
+    result
+}
+
+fn emoji_segments<'a>(text: &str, image_url: Option<&str>) -> Vec<Event<'a>> {
+    let mut segments = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(':') {
+        let candidate = &rest[start + 1..];
+        let emoji = candidate
+            .find(':')
+            .map(|end| &candidate[..end])
+            .filter(|code| {
+                !code.is_empty()
+                    && code
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'))
+            })
+            .and_then(|code| emojis::get_by_shortcode(code).map(|emoji| (code, emoji)));
+
+        let Some((code, emoji)) = emoji else {
+            plain.push_str(&rest[..=start]);
+            rest = candidate;
+            continue;
+        };
+
+        plain.push_str(&rest[..start]);
+        match image_url {
+            Some(url) => {
+                if !plain.is_empty() {
+                    segments.push(Event::Text(std::mem::take(&mut plain).into()));
+                }
+                segments.push(Event::InlineHtml(emoji_image(emoji, code, url).into()));
+            }
+            None => plain.push_str(emoji.as_str()),
+        }
+        rest = &candidate[code.len() + 1..];
+    }
+
+    plain.push_str(rest);
+    if !plain.is_empty() {
+        segments.push(Event::Text(plain.into()));
+    }
+
+    segments
+}
+
+fn emoji_image(emoji: &emojis::Emoji, shortcode: &str, url: &str) -> String {
+    let codepoints = emoji
+        .as_str()
+        .chars()
+        .filter(|&c| c != '\u{fe0f}')
+        .map(|c| format!("{:x}", u32::from(c)))
+        .collect::<Vec<_>>()
+        .join("-");
+
+    format!(
+        r#"<img class="emoji" src="{}" alt="{}" title=":{}:" draggable="false" />"#,
+        escape_html(&url.replace("{code}", &codepoints)),
+        emoji.as_str(),
+        escape_html(shortcode)
+    )
+}
+
+/// Turns paragraphs consisting of a single titled image into figures. The
+/// title becomes the caption and may contain inline Markdown.
+fn wrap_figures<'a>(events: &[Event<'a>], options: &RenderOptions) -> Vec<Event<'a>> {
+    let mut result = Vec::with_capacity(events.len());
+    let mut index = 0;
+
+    while index < events.len() {
+        let Some((end, caption)) = standalone_image(&events[index..]) else {
+            result.push(events[index].clone());
+            index += 1;
+            continue;
+        };
+
+        let caption = render(parser(&caption, options), options).html;
+        let caption = caption.trim();
+        let caption = caption
+            .strip_prefix("<p>")
+            .and_then(|c| c.strip_suffix("</p>"))
+            .unwrap_or(caption);
+
+        result.push(Event::Html("<figure>".into()));
+        // Подпись уже видна, дублировать ее в `title` не нужно
+        for event in &events[index + 1..index + end - 1] {
+            result.push(match event {
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    id,
+                    ..
+                }) => Event::Start(Tag::Image {
+                    link_type: *link_type,
+                    dest_url: dest_url.clone(),
+                    title: CowStr::Borrowed(""),
+                    id: id.clone(),
+                }),
+                event => event.clone(),
+            });
+        }
+        result.push(Event::Html(
+            format!("<figcaption>{caption}</figcaption></figure>\n").into(),
+        ));
+        index += end;
+    }
+
+    result
+}
+
+/// Matches `<p><img title="…"></p>` at the start of `events`, returning the
+/// number of events it spans and the title.
+fn standalone_image(events: &[Event<'_>]) -> Option<(usize, String)> {
+    let [
+        Event::Start(Tag::Paragraph),
+        Event::Start(Tag::Image { title, .. }),
+        rest @ ..,
+    ] = events
+    else {
+        return None;
+    };
+    if title.trim().is_empty() {
+        return None;
+    }
+
+    let image_end = rest
+        .iter()
+        .position(|event| matches!(event, Event::End(TagEnd::Image)))?;
+    matches!(rest.get(image_end + 1), Some(Event::End(TagEnd::Paragraph)))
+        .then(|| (image_end + 4, title.to_string()))
+}
+
+/// Rewrites absolute links to the site itself as root-relative links and
+/// decorates links to other sites according to the external link options.
+fn process_links(events: &mut [Event<'_>], options: &RenderOptions) {
+    let rel: Vec<&str> = [
+        (options.external_links_new_tab, "noopener noreferrer"),
+        (options.external_links_nofollow, "nofollow"),
+        (options.external_links_ugc, "ugc"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, value)| enabled.then_some(value))
+    .collect();
+
+    let decorate =
+        options.external_links_new_tab || !rel.is_empty() || options.external_links_class.is_some();
+    let mut in_external_link = false;
+
+    for event in events.iter_mut() {
+        match event {
+            Event::Start(Tag::Link {
+                dest_url, title, ..
+            }) => {
+                if let Some(path) = internal_path(dest_url, &options.base_url) {
+                    *dest_url = path.into();
+                    continue;
+                }
+                if !decorate || !is_external(dest_url) {
+                    continue;
+                }
+
+                let mut tag = format!(r#"<a href="{}""#, escape_html(dest_url));
+                if !title.is_empty() {
+                    let _ = write!(tag, r#" title="{}""#, escape_html(title));
+                }
+                if let Some(class) = &options.external_links_class {
+                    let _ = write!(tag, r#" class="{}""#, escape_html(class));
+                }
+                if options.external_links_new_tab {
+                    tag.push_str(r#" target="_blank""#);
+                }
+                if !rel.is_empty() {
+                    let _ = write!(tag, r#" rel="{}""#, rel.join(" "));
+                }
+                tag.push('>');
+
+                *event = Event::InlineHtml(tag.into());
+                in_external_link = true;
+            }
+            Event::End(TagEnd::Link) if in_external_link => {
+                *event = Event::InlineHtml("</a>".into());
+                in_external_link = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the root-relative path for an absolute URL under `base_url`.
+fn internal_path(url: &str, base_url: &str) -> Option<String> {
+    if base_url.is_empty() {
+        return None;
+    }
+
+    let rest = url.strip_prefix(base_url)?;
+    match rest.chars().next() {
+        None => Some("/".to_string()),
+        Some('/') => Some(rest.to_string()),
+        Some('?' | '#') => Some(format!("/{rest}")),
+        // `https://example.com.evil` is not the same site
+        Some(_) => None,
+    }
+}
+
+fn is_external(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//")
+}
+
+struct Heading {
+    level: u8,
+    id: String,
+    text: String,
+}
+
+/// Gives every heading a unique id (keeping explicit `{#id}` attributes)
+/// and, given an `anchor_label`, appends a self-link anchor.
+fn assign_heading_ids(events: &mut Vec<Event<'_>>, anchor_label: Option<&str>) -> Vec<Heading> {
+    let mut used_ids = HashSet::new();
+    let mut headings = Vec::new();
+    let mut index = 0;
+
+    while index < events.len() {
+        let Event::Start(Tag::Heading { level, id, .. }) = &events[index] else {
+            index += 1;
+            continue;
+        };
+        let level = heading_level(*level);
+        let explicit_id = id.as_ref().map(ToString::to_string);
+
+        let end = events[index..]
+            .iter()
+            .position(|event| matches!(event, Event::End(TagEnd::Heading(_))))
+            .map_or(events.len(), |offset| index + offset);
+
+        let text = plain_text(&events[index + 1..end]);
+        let base = explicit_id.unwrap_or_else(|| {
+            let slug = slugify(&text);
+            if slug.is_empty() {
+                "section".to_string()
+            } else {
+                slug
+            }
+        });
+        let id = unique_id(&base, &mut used_ids);
+
+        if let Event::Start(Tag::Heading { id: heading_id, .. }) = &mut events[index] {
+            *heading_id = Some(CowStr::from(id.clone()));
+        }
+
+        let mut next = end + 1;
+        if let Some(label) = anchor_label.filter(|_| end < events.len()) {
+            let anchor = format!(
+                r##"<a class="anchor-link" href="#{}" aria-label="{}">#</a>"##,
+                escape_html(&id),
+                escape_html(label)
+            );
+            events.insert(end, Event::InlineHtml(anchor.into()));
+            next += 1;
+        }
+
+        headings.push(Heading { level, id, text });
+        index = next;
+    }
+
+    headings
+}
+
+fn unique_id(base: &str, used_ids: &mut HashSet<String>) -> String {
+    let mut id = base.to_string();
+    let mut counter = 1;
+    while used_ids.contains(&id) {
+        id = format!("{base}-{counter}");
+        counter += 1;
+    }
+    used_ids.insert(id.clone());
+    id
+}
+
+const fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Collects the visible text of inline events.
+fn plain_text(events: &[Event<'_>]) -> String {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+            Event::SoftBreak | Event::HardBreak => Some(" "),
+            _ => None,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Nests headings within `min_level..=max_level` into a tree.
+fn build_toc(headings: &[Heading], min_level: u8, max_level: u8) -> Vec<TocEntry> {
+    let mut root: Vec<TocEntry> = Vec::new();
+
+    for heading in headings
+        .iter()
+        .filter(|h| (min_level..=max_level).contains(&h.level))
+    {
+        let entry = TocEntry {
+            level: heading.level,
+            id: heading.id.clone(),
+            text: heading.text.clone(),
+            children: Vec::new(),
+        };
+
+        // Спускаемся по последним элементам, пока уровень вложенности меньше
+        let mut siblings = &mut root;
+        while siblings.last().is_some_and(|last| last.level < entry.level) {
+            siblings = &mut siblings.last_mut().unwrap().children;
+        }
+        siblings.push(entry);
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_str(markdown: &str, options: &RenderOptions) -> Rendered {
+        render(parser(markdown, options), options)
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("Привет, Мир Ёжиков"), "привет-мир-ёжиков");
+        assert_eq!(slugify("  Rust 2024  "), "rust-2024");
+    }
+
+    #[test]
+    fn test_heading_ids_are_unique() {
+        let rendered = render_str(
+            "# Введение\n\n## Setup\n\n## Setup\n\n## `code` & more\n",
+            &RenderOptions::default(),
+        );
+
+        assert!(rendered.html.contains(r#"<h1 id="введение">"#));
+        assert!(rendered.html.contains(r#"<h2 id="setup">"#));
+        assert!(rendered.html.contains(r#"<h2 id="setup-1">"#));
+        assert!(rendered.html.contains(r#"<h2 id="code-more">"#));
+        assert!(!rendered.html.contains("anchor-link"));
+    }
+
+    #[test]
+    fn test_explicit_heading_id_and_anchor() {
+        let options = RenderOptions {
+            heading_attributes: true,
+            anchor_links: true,
+            anchor_label: "Ссылка на \"раздел\"".to_string(),
+            ..RenderOptions::default()
+        };
+        let rendered = render_str("## Custom {#my-id}\n", &options);
+
+        assert_eq!(
+            rendered.html,
+            "<h2 id=\"my-id\">Custom<a class=\"anchor-link\" href=\"#my-id\" aria-label=\"Ссылка на &quot;раздел&quot;\">#</a></h2>\n"
+        );
+    }
+
+    #[test]
+    fn test_external_links() {
+        let options = RenderOptions {
+            base_url: "https://example.com".to_string(),
+            external_links_new_tab: true,
+            external_links_nofollow: true,
+            external_links_class: Some("external".to_string()),
+            ..RenderOptions::default()
+        };
+        let rendered = render_str(
+            "[Rust](https://rust-lang.org \"Rust\") [Post](https://example.com/posts/a#top) \
+             [Home](https://example.com) [Evil](https://example.com.evil/) [Local](/about)",
+            &options,
+        );
+
+        assert!(rendered.html.contains(
+            r#"<a href="https://rust-lang.org" title="Rust" class="external" target="_blank" rel="noopener noreferrer nofollow">Rust</a>"#
+        ));
+        assert!(rendered.html.contains(r#"<a href="/posts/a#top">Post</a>"#));
+        assert!(rendered.html.contains(r#"<a href="/">Home</a>"#));
+        assert!(
+            rendered.html.contains(
+                r#"<a href="https://example.com.evil/" class="external" target="_blank""#
+            )
+        );
+        assert!(rendered.html.contains(r#"<a href="/about">Local</a>"#));
+    }
+
+    #[test]
+    fn test_external_links_untouched_by_default() {
+        let rendered = render_str("[Rust](https://rust-lang.org)", &RenderOptions::default());
+        assert_eq!(
+            rendered.html,
+            "<p><a href=\"https://rust-lang.org\">Rust</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_emoji_shortcodes() {
+        let options = RenderOptions {
+            emoji: true,
+            ..RenderOptions::default()
+        };
+        let rendered = render_str(
+            "Hi :smile: :unknown_code: 10:30 :+1:\n\n`:smile:`\n\n```\n:rocket:\n```\n",
+            &options,
+        );
+
+        assert_eq!(
+            rendered.html,
+            "<p>Hi 😄 :unknown_code: 10:30 👍</p>\n<p><code>:smile:</code></p>\n<pre><code>:rocket:\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_emoji_images() {
+        let options = RenderOptions {
+            emoji: true,
+            emoji_image_url: Some("/emoji/{code}.svg".to_string()),
+            ..RenderOptions::default()
+        };
+        let rendered = render_str("Ship it :rocket:!", &options);
+
+        assert_eq!(
+            rendered.html,
+            "<p>Ship it <img class=\"emoji\" src=\"/emoji/1f680.svg\" alt=\"🚀\" title=\":rocket:\" draggable=\"false\" />!</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_image_figures() {
+        let options = RenderOptions {
+            figures: true,
+            ..RenderOptions::default()
+        };
+        let markdown = "![A cat](cat.png \"A *fluffy* cat\")\n\nSee ![dog](dog.png \"Dog\") here\n\n![plain](plain.png)\n";
+
+        assert_eq!(
+            render_str(markdown, &options).html,
+            "<figure><img src=\"cat.png\" alt=\"A cat\" /><figcaption>A <em>fluffy</em> cat</figcaption></figure>\n\
+             <p>See <img src=\"dog.png\" alt=\"dog\" title=\"Dog\" /> here</p>\n\
+             <p><img src=\"plain.png\" alt=\"plain\" /></p>\n"
+        );
+        assert_eq!(
+            render_str("![A cat](cat.png \"Caption\")", &RenderOptions::default()).html,
+            "<p><img src=\"cat.png\" alt=\"A cat\" title=\"Caption\" /></p>\n"
+        );
+    }
+
+    #[test]
+    fn test_toc_nesting_and_depth() {
+        let rendered = render_str(
+            "# Title\n\n## One\n\n### One A\n\n#### Deep\n\n### One B\n\n## Two\n",
+            &RenderOptions::default(),
+        );
+
+        let entry = |level, id: &str, text: &str, children| TocEntry {
+            level,
+            id: id.to_string(),
+            text: text.to_string(),
+            children,
+        };
+
+        assert_eq!(
+            rendered.toc,
+            vec![
+                entry(
+                    2,
+                    "one",
+                    "One",
+                    vec![
+                        entry(3, "one-a", "One A", vec![]),
+                        entry(3, "one-b", "One B", vec![]),
+                    ]
+                ),
+                entry(2, "two", "Two", vec![]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! Locale-aware typography: quotes, dashes, ellipses and non-breaking spaces.
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+const NBSP: char = '\u{a0}';
+
+/// Quote characters for a language: outer and nested pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QuoteStyle {
+    outer: (char, char),
+    inner: (char, char),
+}
+
+impl QuoteStyle {
+    fn for_language(language: &str) -> Self {
+        match language {
+            "ru" | "uk" | "be" => Self {
+                outer: ('«', '»'),
+                inner: ('„', '“'),
+            },
+            "de" => Self {
+                outer: ('„', '“'),
+                inner: ('‚', '‘'),
+            },
+            "fr" => Self {
+                outer: ('«', '»'),
+                inner: ('“', '”'),
+            },
+            _ => Self {
+                outer: ('“', '”'),
+                inner: ('‘', '’'),
+            },
+        }
+    }
+}
+
+/// Short words that must not be left at the end of a line.
+fn short_words(language: &str) -> &'static [&'static str] {
+    match language {
+        "ru" => &[
+            "а", "в", "во", "и", "к", "ко", "о", "об", "с", "со", "у", "из", "от", "до", "на",
+            "по", "за", "не", "ни", "но", "же", "ли",
+        ],
+        "uk" => &[
+            "а", "в", "у", "і", "й", "з", "із", "зі", "до", "на", "по", "за", "не", "та", "о",
+        ],
+        "be" => &["а", "і", "у", "ў", "з", "да", "на", "па", "за", "не", "ад"],
+        "en" => &["a", "an", "i", "of", "to", "in", "on", "at", "by"],
+        _ => &[],
+    }
+}
+
+/// Applies typography rules to text events in place.
+///
+/// Code spans and code blocks are left untouched. Quote nesting is tracked
+/// across inline markup within a block, so `"a *"b"* c"` nests correctly.
+pub fn apply(events: &mut [Event<'_>], language: &str, quotes: bool, punctuation: bool) {
+    let language = primary_subtag(language);
+    let style = quotes.then(|| QuoteStyle::for_language(&language));
+    let words = if punctuation {
+        short_words(&language)
+    } else {
+        &[]
+    };
+
+    let mut state = State::default();
+    let mut in_code_block = false;
+
+    for event in events.iter_mut() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Start(tag) if is_block(tag) => state = State::default(),
+            Event::End(tag) if is_block_end(*tag) => state = State::default(),
+            // Код считаем словом, чтобы кавычка после него закрывалась
+            Event::Code(_) => state.previous = Some('x'),
+            Event::SoftBreak | Event::HardBreak => state.previous = Some(' '),
+            Event::Text(text) if !in_code_block => {
+                let mut result = text.to_string();
+                if punctuation {
+                    result = replace_punctuation(&result);
+                }
+                if let Some(style) = style {
+                    result = state.replace_quotes(&result, style);
+                } else {
+                    state.previous = result.chars().last().or(state.previous);
+                }
+                if punctuation {
+                    result = bind_spaces(&result, words);
+                }
+                *text = result.into();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    depth: usize,
+    previous: Option<char>,
+}
+
+impl State {
+    fn replace_quotes(&mut self, text: &str, style: QuoteStyle) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::with_capacity(text.len());
+
+        for (index, &c) in chars.iter().enumerate() {
+            let previous = if index == 0 {
+                self.previous
+            } else {
+                Some(chars[index - 1])
+            };
+            let next = chars.get(index + 1).copied();
+
+            let replacement = match c {
+                '"' => Some(self.double_quote(previous, next, style)),
+                '\'' => Some(single_quote(previous, next, style)),
+                _ => None,
+            };
+            result.push(replacement.unwrap_or(c));
+        }
+
+        self.previous = chars.last().copied().or(self.previous);
+        result
+    }
+
+    /// Chooses an opening or closing quote from the surrounding characters,
+    /// using nested quotes inside an already open quotation.
+    fn double_quote(
+        &mut self,
+        previous: Option<char>,
+        next: Option<char>,
+        style: QuoteStyle,
+    ) -> char {
+        let standalone = next.is_some_and(char::is_whitespace);
+        let opening = is_opening_position(previous) && !(standalone && self.depth > 0);
+
+        if opening {
+            self.depth += 1;
+            if self.depth == 1 {
+                style.outer.0
+            } else {
+                style.inner.0
+            }
+        } else {
+            self.depth = self.depth.saturating_sub(1);
+            if self.depth == 0 {
+                style.outer.1
+            } else {
+                style.inner.1
+            }
+        }
+    }
+}
+
+/// `'` between letters is an apostrophe, otherwise a nested quote.
+fn single_quote(previous: Option<char>, next: Option<char>, style: QuoteStyle) -> char {
+    let after_word = previous.is_some_and(char::is_alphanumeric);
+    let before_word = next.is_some_and(char::is_alphanumeric);
+
+    match (after_word, before_word) {
+        (true, _) => '’',
+        (false, true) => style.inner.0,
+        (false, false) => style.inner.1,
+    }
+}
+
+/// A quote opens at the start of text or after whitespace and opening
+/// punctuation.
+fn is_opening_position(previous: Option<char>) -> bool {
+    previous.is_none_or(|c| {
+        c.is_whitespace()
+            || matches!(
+                c,
+                '(' | '[' | '{' | '«' | '„' | '‚' | '“' | '‘' | '—' | '–' | '-'
+            )
+    })
+}
+
+fn replace_punctuation(text: &str) -> String {
+    text.replace("---", "—")
+        .replace("--", "–")
+        .replace("...", "…")
+}
+
+/// Binds em dashes to the preceding word and short words to the following
+/// one with non-breaking spaces.
+fn bind_spaces(text: &str, words: &[&str]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word = String::new();
+    let mut is_plain_word = true;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ' ' {
+            let before_dash = chars.peek() == Some(&'—');
+            let after_short_word =
+                is_plain_word && !word.is_empty() && words.contains(&word.to_lowercase().as_str());
+            result.push(if before_dash || after_short_word {
+                NBSP
+            } else {
+                ' '
+            });
+            word.clear();
+            is_plain_word = true;
+            continue;
+        }
+
+        if c.is_alphabetic() {
+            word.push(c);
+        } else if !(word.is_empty() && matches!(c, '(' | '«' | '„' | '“' | '‘')) {
+            // Слово с цифрами или пунктуацией после него уже не предлог
+            is_plain_word = false;
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+fn primary_subtag(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+const fn is_block(tag: &Tag<'_>) -> bool {
+    matches!(
+        tag,
+        Tag::Paragraph
+            | Tag::Heading { .. }
+            | Tag::BlockQuote(_)
+            | Tag::Item
+            | Tag::TableCell
+            | Tag::FootnoteDefinition(_)
+    )
+}
+
+const fn is_block_end(tag: TagEnd) -> bool {
+    matches!(
+        tag,
+        TagEnd::Paragraph
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::Item
+            | TagEnd::TableCell
+            | TagEnd::FootnoteDefinition
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Parser, html};
+
+    fn render(markdown: &str, language: &str) -> String {
+        let mut events: Vec<Event> = Parser::new(markdown).collect();
+        apply(&mut events, language, true, true);
+        let mut output = String::new();
+        html::push_html(&mut output, events.into_iter());
+        output
+    }
+
+    #[test]
+    fn test_russian_quotes() {
+        assert_eq!(
+            render(r#"Он сказал: "Читай "Войну и мир" вслух""#, "ru"),
+            "<p>Он сказал: «Читай „Войну и\u{a0}мир“ вслух»</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_english_quotes_and_apostrophes() {
+        assert_eq!(
+            render(r#"It's "quoted 'inner' text""#, "en-US"),
+            "<p>It’s “quoted ‘inner’ text”</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_quotes_across_inline_markup() {
+        assert_eq!(
+            render(r#""*важно*" и "`code`""#, "ru"),
+            "<p>«<em>важно</em>» и\u{a0}«<code>code</code>»</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_dashes_and_short_words() {
+        assert_eq!(
+            render("Москва --- столица... В городе с 1147 года", "ru"),
+            "<p>Москва\u{a0}— столица… В\u{a0}городе с\u{a0}1147 года</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_code_untouched() {
+        assert_eq!(
+            render("`\"a\" -- b`\n\n```\n\"x\" --- y...\n```\n", "ru"),
+            "<p><code>\"a\" -- b</code></p>\n<pre><code>\"x\" --- y...\n</code></pre>\n"
+        );
+    }
+}
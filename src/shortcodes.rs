@@ -0,0 +1,493 @@
+//! Shortcodes: components embedded in Markdown.
+//!
+//! A shortcode is either self-contained or wraps Markdown content:
+//!
+//! ```text
+//! {{< youtube id="dQw4w9WgXcQ" >}}
+//!
+//! {{< notice type="warning" title="Heads up" >}}
+//! Inner **Markdown** content.
+//! {{< /notice >}}
+//! ```
+//!
+//! Each shortcode is rendered by the `shortcodes/<name>` template. Named
+//! arguments become top-level context keys and the rendered inner content
+//! is available as `inner`. `{{</* name */>}}` is output literally, so
+//! shortcodes can be documented in posts.
+
+use crate::error::{Result, ZahuyachError};
+use serde_json::{Map, Value};
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::Path;
+
+/// Directory inside `templates_dir` holding shortcode templates.
+pub const SHORTCODES_DIR: &str = "shortcodes";
+
+/// Renders shortcode templates.
+pub trait ShortcodeRenderer {
+    /// Renders shortcode `name` with `context`, or returns `None` if no
+    /// such shortcode exists.
+    fn render_shortcode(&self, name: &str, context: &Value) -> Option<Result<String>>;
+}
+
+/// Markdown with shortcodes replaced by placeholders.
+#[derive(Debug, Default)]
+pub struct Expanded {
+    pub markdown: String,
+    /// Placeholders and the rendered HTML replacing them
+    shortcodes: Vec<(String, String)>,
+}
+
+impl Expanded {
+    /// Wraps Markdown that has no shortcodes to expand.
+    #[must_use]
+    pub const fn unchanged(markdown: String) -> Self {
+        Self {
+            markdown,
+            shortcodes: Vec::new(),
+        }
+    }
+
+    /// Replaces placeholders in rendered HTML with the shortcode output.
+    #[must_use]
+    pub fn inject(&self, html: &str) -> String {
+        self.shortcodes
+            .iter()
+            .fold(html.to_string(), |html, (placeholder, output)| {
+                html.replace(placeholder, output)
+            })
+    }
+}
+
+/// Location of the content being expanded, for error messages.
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'a> {
+    pub path: &'a Path,
+    /// Line of the file on which the Markdown starts
+    pub first_line: usize,
+}
+
+/// Replaces shortcodes in `markdown` with placeholder comments and renders
+/// them. `render_markdown` turns inner content into HTML.
+///
+/// The placeholders are HTML comments, so Markdown passes them through
+/// untouched; [`Expanded::inject`] swaps in the output afterwards. They
+/// span as many lines as the shortcode did, keeping line numbers of the
+/// following content intact.
+///
+/// # Errors
+///
+/// Returns an error naming the file and line for unknown or malformed
+/// shortcodes and for template errors.
+pub fn expand(
+    markdown: &str,
+    source: Source<'_>,
+    renderer: &dyn ShortcodeRenderer,
+    render_markdown: &dyn Fn(&str) -> String,
+) -> Result<Expanded> {
+    let code = code_ranges(markdown);
+    let mut expanded = Expanded::default();
+    let mut position = 0;
+
+    while let Some(start) = find_tag(markdown, position, &code) {
+        expanded.markdown.push_str(&markdown[position..start]);
+        let line = source.first_line + markdown[..start].matches('\n').count();
+        let error = |message: String| {
+            ZahuyachError::InvalidInput(format!(
+                "{message} in '{}' at line {line}",
+                source.path.display()
+            ))
+        };
+
+        let tag = Tag::parse(markdown, start).map_err(error)?;
+        if tag.escaped {
+            let _ = write!(expanded.markdown, "{{{{<{}>}}}}", tag.body);
+            position = tag.end;
+            continue;
+        }
+        if tag.closing {
+            return Err(error(format!(
+                "Unexpected closing shortcode '{}'",
+                tag.name
+            )));
+        }
+
+        let mut context = tag.arguments().map_err(error)?;
+        position = tag.end;
+
+        if let Some((inner, closing)) = find_closing(markdown, &tag, &code) {
+            let inner_source = Source {
+                path: source.path,
+                first_line: line + markdown[start..inner.start].matches('\n').count(),
+            };
+            let nested = expand(&markdown[inner], inner_source, renderer, render_markdown)?;
+            let inner_html = nested.inject(&render_markdown(&nested.markdown));
+            context.insert("inner".to_string(), Value::String(inner_html));
+            position = closing;
+        }
+
+        let html = renderer
+            .render_shortcode(&tag.name, &Value::Object(context))
+            .ok_or_else(|| error(format!("Unknown shortcode '{}'", tag.name)))?
+            .map_err(|e| match e {
+                ZahuyachError::InvalidInput(message) => error(message),
+                e @ ZahuyachError::Io(_) => e,
+            })?;
+
+        let lines = markdown[start..position].matches('\n').count();
+        let placeholder = format!(
+            "<!--zahuyach-shortcode-{}{}-->",
+            expanded.shortcodes.len(),
+            "\n".repeat(lines)
+        );
+        expanded.markdown.push_str(&placeholder);
+        expanded.shortcodes.push((placeholder, html));
+    }
+
+    expanded.markdown.push_str(&markdown[position..]);
+    Ok(expanded)
+}
+
+/// A parsed `{{< … >}}` tag.
+struct Tag<'a> {
+    name: String,
+    body: &'a str,
+    closing: bool,
+    escaped: bool,
+    /// Byte offset just past the tag
+    end: usize,
+}
+
+impl<'a> Tag<'a> {
+    fn parse(markdown: &'a str, start: usize) -> std::result::Result<Self, String> {
+        let open = start + "{{<".len();
+        let length = markdown[open..]
+            .find(">}}")
+            .ok_or_else(|| "Unclosed shortcode tag".to_string())?;
+        let body = &markdown[open..open + length];
+        let trimmed = body.trim();
+
+        let escaped = trimmed.starts_with("/*") && trimmed.ends_with("*/");
+        let closing = !escaped && trimmed.starts_with('/');
+        let name = trimmed
+            .trim_start_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+
+        if !escaped && !is_valid_name(&name) {
+            return Err(format!("Invalid shortcode name '{name}'"));
+        }
+
+        Ok(Self {
+            name,
+            body: if escaped {
+                &trimmed[2..trimmed.len() - 2]
+            } else {
+                body
+            },
+            closing,
+            escaped,
+            end: open + length + ">}}".len(),
+        })
+    }
+
+    /// Parses `key=value` arguments; values are quoted strings, numbers,
+    /// booleans or bare words.
+    fn arguments(&self) -> std::result::Result<Map<String, Value>, String> {
+        let mut arguments = Map::new();
+        let body = self.body.trim();
+        let mut rest = body
+            .strip_prefix(self.name.as_str())
+            .unwrap_or(body)
+            .trim_start();
+
+        while !rest.is_empty() {
+            let (key, value) = rest
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value argument, found '{rest}'"))?;
+            let key = key.trim();
+            if !is_valid_name(key) {
+                return Err(format!("Invalid argument name '{key}'"));
+            }
+
+            let value = value.trim_start();
+            let (value, remainder) = if let Some(quoted) = value.strip_prefix('"') {
+                parse_quoted(quoted)
+                    .ok_or_else(|| format!("Unterminated string for argument '{key}'"))?
+            } else {
+                let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                (parse_bare(&value[..end]), &value[end..])
+            };
+
+            arguments.insert(key.to_string(), value);
+            rest = remainder.trim_start();
+        }
+
+        Ok(arguments)
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Parses a double-quoted string (after the opening quote), returning it
+/// and the text following the closing quote.
+fn parse_quoted(text: &str) -> Option<(Value, &str)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((Value::String(value), &text[index + 1..])),
+            '\\' => value.push(chars.next()?.1),
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+fn parse_bare(text: &str) -> Value {
+    match text {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => text
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| text.parse::<f64>().map(Value::from))
+            .unwrap_or_else(|_| Value::String(text.to_string())),
+    }
+}
+
+/// Finds the next `{{<` at or after `from` that is outside code.
+fn find_tag(markdown: &str, from: usize, code: &[Range<usize>]) -> Option<usize> {
+    let mut from = from;
+    loop {
+        let start = from + markdown[from..].find("{{<")?;
+        match code.iter().find(|range| range.contains(&start)) {
+            Some(range) => from = range.end,
+            None => return Some(start),
+        }
+    }
+}
+
+/// Finds the `{{< /name >}}` matching `tag`, accounting for nested
+/// shortcodes of the same name. Returns the inner range and the offset
+/// past the closing tag, or `None` for a self-contained shortcode.
+fn find_closing(
+    markdown: &str,
+    tag: &Tag<'_>,
+    code: &[Range<usize>],
+) -> Option<(Range<usize>, usize)> {
+    let mut depth = 0;
+    let mut position = tag.end;
+
+    while let Some(start) = find_tag(markdown, position, code) {
+        let candidate = Tag::parse(markdown, start).ok()?;
+        position = candidate.end;
+
+        if candidate.escaped || candidate.name != tag.name {
+            continue;
+        }
+        if !candidate.closing {
+            depth += 1;
+        } else if depth == 0 {
+            return Some((tag.end..start, candidate.end));
+        } else {
+            depth -= 1;
+        }
+    }
+
+    None
+}
+
+/// Byte ranges of fenced code blocks and inline code spans, where
+/// shortcodes are not expanded.
+fn code_ranges(markdown: &str) -> Vec<Range<usize>> {
+    let mut ranges = fenced_code_ranges(markdown);
+    let spans = code_span_ranges(markdown, &ranges);
+    ranges.extend(spans);
+    ranges
+}
+
+/// Byte ranges of fenced code blocks.
+fn fenced_code_ranges(markdown: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut fence: Option<(usize, &str)> = None;
+    let mut offset = 0;
+
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some((offset, marker)),
+            (Some((start, open)), Some(marker)) if marker == open => {
+                ranges.push(start..offset + line.len());
+                fence = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+
+    if let Some((start, _)) = fence {
+        ranges.push(start..markdown.len());
+    }
+    ranges
+}
+
+/// Byte ranges of inline code spans outside `fenced` blocks: a run of
+/// backticks up to the next run of the same length. Unmatched runs are
+/// literal backticks.
+fn code_span_ranges(markdown: &str, fenced: &[Range<usize>]) -> Vec<Range<usize>> {
+    // Серии обратных кавычек (начало, длина); `None` - блок кода между ними
+    let mut runs: Vec<Option<(usize, usize)>> = Vec::new();
+    let bytes = markdown.as_bytes();
+    let mut position = 0;
+
+    while position < bytes.len() {
+        if let Some(range) = fenced.iter().find(|range| range.contains(&position)) {
+            runs.push(None);
+            position = range.end;
+        } else if bytes[position] == b'`' {
+            let start = position;
+            while bytes.get(position) == Some(&b'`') {
+                position += 1;
+            }
+            runs.push(Some((start, position - start)));
+        } else {
+            position += 1;
+        }
+    }
+
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index < runs.len() {
+        if let Some((start, length)) = runs[index] {
+            let closing = runs[index + 1..]
+                .iter()
+                .map_while(|run| *run)
+                .position(|(_, closing_length)| closing_length == length);
+            if let Some(offset) = closing {
+                index += offset + 1;
+                if let Some((end, _)) = runs[index] {
+                    ranges.push(start..end + length);
+                }
+            }
+        }
+        index += 1;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct TestRenderer;
+
+    impl ShortcodeRenderer for TestRenderer {
+        fn render_shortcode(&self, name: &str, context: &Value) -> Option<Result<String>> {
+            match name {
+                "youtube" => Some(Ok(format!(
+                    "<iframe data-id=\"{}\"></iframe>",
+                    context["id"].as_str().unwrap_or_default()
+                ))),
+                "notice" => Some(Ok(format!(
+                    "<aside class=\"{}\">{}</aside>",
+                    context["type"].as_str().unwrap_or_default(),
+                    context["inner"].as_str().unwrap_or_default()
+                ))),
+                "args" => Some(Ok(context.to_string())),
+                _ => None,
+            }
+        }
+    }
+
+    fn expand_str(markdown: &str) -> Result<String> {
+        let source = Source {
+            path: Path::new("post.md"),
+            first_line: 5,
+        };
+        let render = |markdown: &str| format!("<p>{}</p>", markdown.trim());
+        let expanded = expand(markdown, source, &TestRenderer, &render)?;
+        Ok(expanded.inject(&expanded.markdown))
+    }
+
+    #[test]
+    fn test_inline_shortcode() {
+        assert_eq!(
+            expand_str("Watch {{< youtube id=\"abc\" >}} now").unwrap(),
+            "Watch <iframe data-id=\"abc\"></iframe> now"
+        );
+    }
+
+    #[test]
+    fn test_nested_shortcodes_with_inner_content() {
+        let markdown = "{{< notice type=\"warning\" >}}\nOuter {{< notice type=\"tip\" >}}inner{{< /notice >}}\n{{< /notice >}}";
+        assert_eq!(
+            expand_str(markdown).unwrap(),
+            "<aside class=\"warning\"><p>Outer <aside class=\"tip\"><p>inner</p></aside></p></aside>"
+        );
+    }
+
+    #[test]
+    fn test_arguments() {
+        let output =
+            expand_str(r#"{{< args title="Say \"hi\"" count=3 ratio=1.5 on=true mode=dark >}}"#)
+                .unwrap();
+        let context: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            context,
+            json!({"title": "Say \"hi\"", "count": 3, "ratio": 1.5, "on": true, "mode": "dark"})
+        );
+    }
+
+    #[test]
+    fn test_code_and_escaped_shortcodes_are_literal() {
+        let markdown = "```\n{{< unknown >}}\n```\n\nUse {{</* youtube id=\"x\" */>}}";
+        assert_eq!(
+            expand_str(markdown).unwrap(),
+            "```\n{{< unknown >}}\n```\n\nUse {{< youtube id=\"x\" >}}"
+        );
+    }
+
+    #[test]
+    fn test_inline_code_shortcodes_are_literal() {
+        let markdown = "Use `{{< unknown >}}` or ``{{< tweet >}} ` `` in code, \
+                        but a stray ` keeps {{< youtube id=\"a\" >}}";
+        assert_eq!(
+            expand_str(markdown).unwrap(),
+            "Use `{{< unknown >}}` or ``{{< tweet >}} ` `` in code, \
+             but a stray ` keeps <iframe data-id=\"a\"></iframe>"
+        );
+    }
+
+    #[test]
+    fn test_unknown_shortcode_reports_line() {
+        let error = expand_str("Intro\n\n{{< tweet id=1 >}}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Error: Invalid input: Unknown shortcode 'tweet' in 'post.md' at line 7"
+        );
+    }
+
+    #[test]
+    fn test_malformed_shortcodes() {
+        assert!(expand_str("{{< youtube id=\"abc >}}").is_err());
+        assert!(expand_str("{{< youtube abc >}}").is_err());
+        assert!(expand_str("{{< /notice >}}").is_err());
+        assert!(expand_str("{{< youtube id=1").is_err());
+    }
+}
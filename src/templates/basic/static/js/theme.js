@@ -145,6 +145,8 @@
 
     // Copy code blocks to clipboard
     const setupCodeCopy = () => {
+        if (!document.documentElement.hasAttribute('data-code-copy')) return;
+
         document.querySelectorAll('pre').forEach(pre => {
             // Create copy button
             const btn = document.createElement('button');
@@ -154,7 +156,7 @@
 
             // Copy functionality
             btn.addEventListener('click', async () => {
-                const code = pre.textContent;
+                const code = (pre.querySelector('code') || pre).textContent;
                 try {
                     await navigator.clipboard.writeText(code);
                     btn.classList.add('copied');
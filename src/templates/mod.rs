@@ -1,12 +1,44 @@
 use crate::error::{Result, ZahuyachError};
 use rust_embed::RustEmbed;
+use std::borrow::Cow;
 use std::fs;
 use std::path::Path;
 
+/// Directory with Handlebars templates inside a theme.
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Directory with static files inside a theme.
+pub const STATIC_DIR: &str = "static";
+
+/// Directory with `<lang>.toml` translations inside a theme.
+pub const I18N_DIR: &str = "i18n";
+
+/// Directory in a project where the theme files it was created or last
+/// upgraded with are recorded, for three-way merges on upgrade.
+pub const THEME_BASE_DIR: &str = ".zahuyach/theme";
+
+/// File in [`THEME_BASE_DIR`] with the zahuyach version of the recorded theme.
+const THEME_VERSION_FILE: &str = "VERSION";
+
 #[derive(RustEmbed)]
 #[folder = "src/templates/basic"]
 pub struct BasicTemplate;
 
+/// Returns the embedded basic theme files under `dir`, with paths relative
+/// to it. This is the fallback for anything a theme or project doesn't
+/// provide.
+#[must_use]
+pub fn embedded_files(dir: &str) -> Vec<(String, Cow<'static, [u8]>)> {
+    let prefix = format!("{dir}/");
+    BasicTemplate::iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(&prefix)?.to_string();
+            let file = BasicTemplate::get(&path)?;
+            Some((relative, file.data))
+        })
+        .collect()
+}
+
 /// Copies the basic template to the specified directory
 pub fn copy_basic_template<P: AsRef<Path>>(target_dir: P) -> Result<()> {
     let target_dir = target_dir.as_ref();
@@ -29,11 +61,49 @@ pub fn copy_basic_template<P: AsRef<Path>>(target_dir: P) -> Result<()> {
         }
 
         // Write the file content
-        fs::write(&target_file_path, file_data.data).map_err(|e| ZahuyachError::Io(e))?;
+        fs::write(&target_file_path, &file_data.data).map_err(ZahuyachError::Io)?;
 
         println!("Created: {}", target_file_path.display());
+
+        if theme_files().iter().any(|f| f == file_path.as_ref()) {
+            let base_path = target_dir.join(THEME_BASE_DIR).join(file_path.as_ref());
+            if let Some(parent) = base_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(base_path, file_data.data)?;
+        }
     }
 
+    record_theme_version(target_dir)
+}
+
+/// Returns the embedded files that make up the theme: templates, static
+/// files and translations, as paths like `templates/post.html`.
+#[must_use]
+pub fn theme_files() -> Vec<String> {
+    BasicTemplate::iter()
+        .filter(|path| {
+            path.starts_with(&format!("{TEMPLATES_DIR}/"))
+                || path.starts_with(&format!("{STATIC_DIR}/"))
+                || path.starts_with(&format!("{I18N_DIR}/"))
+        })
+        .map(|path| path.to_string())
+        .collect()
+}
+
+/// Records the current zahuyach version as the version of the project's
+/// theme files.
+///
+/// # Errors
+///
+/// Returns an error if the version file can't be written.
+pub fn record_theme_version(project_dir: &Path) -> Result<()> {
+    let base_dir = project_dir.join(THEME_BASE_DIR);
+    fs::create_dir_all(&base_dir)?;
+    fs::write(
+        base_dir.join(THEME_VERSION_FILE),
+        format!("{}\n", env!("CARGO_PKG_VERSION")),
+    )?;
     Ok(())
 }
 
@@ -65,5 +135,14 @@ mod tests {
         // Check that config.toml was created
         let config_path = temp_dir.path().join("config.toml");
         assert!(config_path.exists());
+
+        // Theme files are recorded for later upgrades
+        let base_dir = temp_dir.path().join(THEME_BASE_DIR);
+        assert!(base_dir.join("templates/post.html").exists());
+        assert!(!base_dir.join("config.toml").exists());
+        assert_eq!(
+            fs::read_to_string(base_dir.join(THEME_VERSION_FILE)).unwrap(),
+            format!("{}\n", env!("CARGO_PKG_VERSION"))
+        );
     }
 }
@@ -1,6 +1,6 @@
 use clap::Parser;
 use rstest::rstest;
-use zahuyach::cli::{Cli, Commands};
+use zahuyach::cli::{Cli, Commands, ThemeCommands};
 
 #[rstest]
 #[case(vec!["zahuyach", "init", "my-blog"], Commands::Init { name: Some("my-blog".to_string()) })]
@@ -13,24 +13,44 @@ fn test_cli_init_command(#[case] args: Vec<&str>, #[case] expected: Commands) {
 #[rstest]
 #[case(
     vec!["zahuyach", "build"],
-    Commands::Build { dir: "dist".to_string() },
+    Commands::Build {
+        dir: "dist".to_string(),
+        strict: false,
+    },
     "default output directory"
 )]
 #[case(
     vec!["zahuyach", "build", "--dir", "public"],
-    Commands::Build { dir: "public".to_string() },
+    Commands::Build {
+        dir: "public".to_string(),
+        strict: false,
+    },
     "long flag custom output directory"
 )]
 #[case(
     vec!["zahuyach", "build", "-d", "public"],
-    Commands::Build { dir: "public".to_string() },
+    Commands::Build {
+        dir: "public".to_string(),
+        strict: false,
+    },
     "short flag custom output directory"
 )]
 #[case(
     vec!["zahuyach", "build", "--dir", "output"],
-    Commands::Build { dir: "output".to_string() },
+    Commands::Build {
+        dir: "output".to_string(),
+        strict: false,
+    },
     "long flag different custom directory"
 )]
+#[case(
+    vec!["zahuyach", "build", "--strict"],
+    Commands::Build {
+        dir: "dist".to_string(),
+        strict: true,
+    },
+    "strict templates"
+)]
 fn test_cli_build_command(
     #[case] args: Vec<&str>,
     #[case] expected: Commands,
@@ -75,9 +95,67 @@ fn test_cli_serve_command(
     assert_eq!(cli.get_command(), &expected, "Failed for: {}", description);
 }
 
+#[rstest]
+#[case(
+    vec!["zahuyach", "theme", "eject"],
+    Commands::Theme { action: ThemeCommands::Eject { file: None, force: false } },
+    "eject whole theme"
+)]
+#[case(
+    vec!["zahuyach", "theme", "eject", "post.html", "--force"],
+    Commands::Theme { action: ThemeCommands::Eject { file: Some("post.html".to_string()), force: true } },
+    "eject single file with force"
+)]
+#[case(
+    vec!["zahuyach", "theme", "diff", "static/css/main.css"],
+    Commands::Theme { action: ThemeCommands::Diff { file: Some("static/css/main.css".to_string()) } },
+    "diff single file"
+)]
+#[case(
+    vec!["zahuyach", "theme", "upgrade"],
+    Commands::Theme { action: ThemeCommands::Upgrade },
+    "upgrade"
+)]
+fn test_cli_theme_command(
+    #[case] args: Vec<&str>,
+    #[case] expected: Commands,
+    #[case] description: &str,
+) {
+    let cli = Cli::try_parse_from(args).unwrap();
+    assert_eq!(cli.get_command(), &expected, "Failed for: {}", description);
+}
+
+#[rstest]
+#[case(
+    vec!["zahuyach", "context", "post", "hello-world"],
+    Commands::Context { page: Some("post".to_string()), name: Some("hello-world".to_string()), lang: None, schema: false },
+    "post by slug"
+)]
+#[case(
+    vec!["zahuyach", "context", "index", "--lang", "en"],
+    Commands::Context { page: Some("index".to_string()), name: None, lang: Some("en".to_string()), schema: false },
+    "page in another language"
+)]
+#[case(
+    vec!["zahuyach", "context", "--schema"],
+    Commands::Context { page: None, name: None, lang: None, schema: true },
+    "schema of every page type"
+)]
+fn test_cli_context_command(
+    #[case] args: Vec<&str>,
+    #[case] expected: Commands,
+    #[case] description: &str,
+) {
+    let cli = Cli::try_parse_from(args).unwrap();
+    assert_eq!(cli.get_command(), &expected, "Failed for: {}", description);
+}
+
 #[rstest]
 #[case(vec!["zahuyach"], "missing command")]
+#[case(vec!["zahuyach", "theme"], "missing theme action")]
 #[case(vec!["zahuyach", "invalid"], "invalid command")]
+#[case(vec!["zahuyach", "context"], "missing page type")]
+#[case(vec!["zahuyach", "context", "tag", "--schema"], "page type with schema")]
 #[case(vec!["zahuyach", "serve", "--port", "invalid"], "invalid port number")]
 #[case(vec!["zahuyach", "serve", "-p", "70000"], "port number out of range")]
 fn test_cli_invalid_arguments(#[case] args: Vec<&str>, #[case] description: &str) {